@@ -4,7 +4,7 @@ fn main() {
     let target = std::path::Path::new(r#"C:\Users\Fraser Balch\source\generative_target\src\lib.rs"#);
 
     let system = Arena::fixed("System")
-        .add_component(ComponentType::dense("name", "String"))
+        .add_component(ComponentType::dense("name", "String").unique())
         .add_component(ComponentType::dense_from_type("Position"))
         .add_component(ComponentType::dense("radius", "Length"))
         .add_component(ComponentType::dense_from_type("Temperature"))
@@ -13,9 +13,13 @@ fn main() {
     let mut body = Arena::fixed("Body")
         .add_component(ComponentType::sparse("name", "String"))
         .add_component(ComponentType::dense("parameters", "OrbitParameters"))
-        .add_component(ComponentType::sparse("parent", "Id<Body>"))
-        .add_default_component(ComponentType::dense_from_type("Position"))
-        .add_default_component(ComponentType::dense("relative_pos", "Position"));
+        .add_default_component(ComponentType::double_buffered("position", "Position"))
+        .add_default_component(ComponentType::dense("relative_pos", "Position"))
+        .add_default_component(ComponentType::history("orbit_trail", "Position", 64))
+        .add_default_component(ComponentType::tag("is_selected"))
+        .add_hierarchy()
+        .add_events()
+        .add_filter("habitable", &["surface", "atmosphere"]);
 
     let mut surface = Arena::fixed("Surface")
         .add_component(ComponentType::dense_from_type("Area"))
@@ -25,7 +29,7 @@ fn main() {
         .add_component(ComponentType::dense("breathability", "bool"))
         .add_component(ComponentType::dense_from_type("GreenhouseRatio"));
 
-    body.add_reference(&system, LinkType::Required);
+    body.add_reference_with_edge(&system, LinkType::Required, "Length");
 
     body.add_ownership(&surface, LinkType::Optional);
     surface.add_reference(&body, LinkType::Required);
@@ -35,12 +39,17 @@ fn main() {
 
     let mut world = World::new()
         .add_use("physics::*")
+        .add_journal()
         .add_static_component(StaticComponent::from_type("Time"))
         .add_static_component(StaticComponent::from_type("Starfield"))
-        .add_arena(system)
+        .add_arena(system.clone())
         .add_arena(body.clone())
         .add_arena(surface.clone())
         .add_arena(atmosphere.clone())
+        .add_system(System::new("update_orbits")
+            .reads(&body, "parameters")
+            .writes(&body, "relative_pos"))
+        .add_relation(Relation::new("jump_routes", &system, &system))
         ;
 
     let planet = CompoundEntity::new("Planet", &body, vec![&surface, &atmosphere]);
@@ -0,0 +1,38 @@
+use generative_ecs::*;
+use std::path::PathBuf;
+
+fn main() {
+    let out = PathBuf::from(std::env::var("OUT_DIR").unwrap());
+
+    std::fs::write(out.join("fleet.rs"), fleet().to_string()).unwrap();
+
+    println!("cargo:rerun-if-changed=build.rs");
+}
+
+fn fleet() -> World {
+    let system = Arena::fixed("System")
+        .add_component(ComponentType::dense("name", "String").unique());
+
+    let engine = Arena::generational("Engine")
+        .add_component(ComponentType::history("heat", "f64", 4));
+
+    let mut crew = Arena::generational("Crew")
+        .add_component(ComponentType::dense("rank", "u8"));
+    crew.add_reference(&engine, LinkType::Optional);
+
+    let mut ship = Arena::generational("Ship")
+        .add_component(ComponentType::history("speed", "f64", 4))
+        .add_default_component(ComponentType::dense("heading", "f64"))
+        .add_hierarchy()
+        .add_events();
+    ship.add_ownership(&engine, LinkType::Optional);
+    ship.add_ownership(&crew, LinkType::Optional);
+    ship.add_reference(&system, LinkType::Optional);
+
+    World::new()
+        .add_arena(system)
+        .add_arena(engine)
+        .add_arena(crew)
+        .add_arena(ship)
+        .add_journal()
+}
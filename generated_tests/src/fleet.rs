@@ -0,0 +1,220 @@
+#![allow(dead_code, unused_imports, clippy::all)]
+
+include!(concat!(env!("OUT_DIR"), "/fleet.rs"));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ship(speed: f64) -> ShipRow {
+        ShipRow::builder().speed(speed).build()
+    }
+
+    fn system(name: &str) -> SystemRow {
+        SystemRow::builder().name(name).build()
+    }
+
+    #[test]
+    fn undone_create_is_redone_with_the_same_id() {
+        let mut world = World::default();
+
+        let id = world.create_ship(ship(1.0));
+
+        assert!(world.undo());
+        assert!(world.allocators.ship.verify(id).is_none());
+
+        assert!(world.redo());
+        assert!(world.allocators.ship.verify(id).is_some());
+    }
+
+    #[test]
+    fn undone_kill_revives_the_same_id_with_its_history_and_hierarchy() {
+        let mut world = World::default();
+
+        let parent = world.create_ship(ship(1.0));
+        let child = world.create_ship(ship(5.0));
+
+        {
+            let parent = world.allocators.ship.verify(parent).unwrap();
+            let child = world.allocators.ship.verify(child).unwrap();
+
+            world.state.ship.push_speed(parent, 2.0);
+            world.state.ship.set_parent(child, parent).unwrap();
+        }
+
+        world.kill_ship(parent);
+        assert!(world.allocators.ship.verify(parent).is_none());
+        assert!(world.state.ship.hierarchy.parent(child).is_none());
+
+        assert!(world.undo());
+
+        let valid = world.allocators.ship.verify(parent).unwrap();
+        assert_eq!(vec![1.0, 2.0], world.state.ship.iter_speed_history(valid).copied().collect::<Vec<_>>());
+        assert_eq!(&[child], world.state.ship.hierarchy.children(parent));
+    }
+
+    #[test]
+    fn undone_insert_restores_default_components() {
+        let mut world = World::default();
+
+        let id = world.create_ship(ship(1.0));
+        world.set_ship_heading(id, 90.0);
+        world.insert_ship(id, ship(2.0));
+
+        assert_eq!(0.0, world.state.ship.heading[world.allocators.ship.verify(id).unwrap()]);
+
+        assert!(world.undo());
+        assert_eq!(90.0, world.state.ship.heading[world.allocators.ship.verify(id).unwrap()]);
+    }
+
+    #[test]
+    fn unique_setter_rejects_a_name_in_use_and_undoes_the_index() {
+        let mut world = World::default();
+
+        let sol = world.create_system(system("Sol")).unwrap();
+        let vega = world.create_system(system("Vega")).unwrap();
+
+        assert!(world.set_system_name(vega, String::from("Sol")).is_err());
+
+        world.set_system_name(sol, String::from("Altair")).unwrap();
+        assert_eq!(Some(sol), world.state.system.find_by_name(&String::from("Altair")));
+
+        assert!(world.undo());
+        assert_eq!(Some(sol), world.state.system.find_by_name(&String::from("Sol")));
+        assert_eq!(None, world.state.system.find_by_name(&String::from("Altair")));
+    }
+
+    #[test]
+    fn snapshots_only_copy_arenas_written_after_the_save() {
+        let mut world = World::default();
+        let mut snapshots = Snapshots::new(4);
+
+        world.create_ship(ship(1.0));
+        snapshots.save(1, &world);
+
+        world.create_system(system("Sol")).unwrap();
+
+        let snapshot = snapshots.get(1).unwrap();
+
+        assert!(Shared::ptr_eq(&world.state.ship, &snapshot.state.ship));
+        assert!(Shared::ptr_eq(&world.allocators.ship, &snapshot.allocators.ship));
+        assert!(!Shared::ptr_eq(&world.state.system, &snapshot.state.system));
+        assert_eq!(1, world.allocators.system.len());
+        assert_eq!(0, snapshot.allocators.system.len());
+    }
+
+    #[test]
+    fn linking_over_an_optional_link_unlinks_the_replaced_entity() {
+        let mut world = World::default();
+
+        let id = world.create_ship(ship(1.0));
+        let sol = world.create_system(system("Sol")).unwrap();
+        let vega = world.create_system(system("Vega")).unwrap();
+
+        {
+            let valid = world.allocators.ship.verify(id).unwrap();
+
+            Link::<Ship, System>::link(&mut world.state, valid, &sol, ());
+            Link::<Ship, System>::link(&mut world.state, valid, &vega, ());
+        }
+
+        let events = world.state.ship.events.drain().collect::<Vec<_>>();
+
+        assert_eq!(vec![
+            Event::Created(id),
+            Event::Linked(id, ShipLink::System(sol)),
+            Event::Unlinked(id, ShipLink::System(sol)),
+            Event::Linked(id, ShipLink::System(vega)),
+        ], events);
+    }
+
+    #[test]
+    fn commands_replay_create_link_and_kill() {
+        let mut world = World::default();
+        let mut commands = world.commands();
+
+        let sol = commands.create_system(system("Sol"));
+        let a = commands.create_ship(ship(1.0));
+        let b = commands.create_ship(ship(2.0));
+        commands.link_ship_system(a, sol);
+        commands.kill_ship(b);
+
+        world.apply(commands).unwrap();
+
+        let valid = world.allocators.ship.verify(a).unwrap();
+        assert_eq!(Some(sol), world.state.ship.system[valid]);
+        assert!(world.allocators.ship.verify(b).is_none());
+    }
+
+    #[test]
+    fn commands_taken_before_an_apply_go_stale() {
+        let mut world = World::default();
+        let mut first = world.commands();
+        let mut second = world.commands();
+
+        first.create_ship(ship(1.0));
+        second.create_ship(ship(2.0));
+
+        assert_eq!(Ok(()), world.apply(first));
+        assert_eq!(Err(CommandError::StaleReservation { arena: "Ship" }), world.apply(second));
+        assert_eq!(1, world.allocators.ship.len());
+    }
+
+    #[test]
+    fn commands_can_be_sent_to_another_thread() {
+        let mut world = World::default();
+        let commands = world.commands();
+
+        let commands = std::thread::spawn(move || {
+            let mut commands = commands;
+            commands.create_ship(ship(1.0));
+            commands
+        }).join().unwrap();
+
+        world.apply(commands).unwrap();
+        assert_eq!(1, world.allocators.ship.len());
+    }
+
+    #[test]
+    fn undone_create_and_kill_push_events() {
+        let mut world = World::default();
+
+        let id = world.create_ship(ship(1.0));
+        world.kill_ship(id);
+        world.undo();
+        world.undo();
+
+        let events = world.state.ship.events.drain().collect::<Vec<_>>();
+
+        assert_eq!(vec![Event::Created(id), Event::Killed(id), Event::Created(id), Event::Killed(id)], events);
+    }
+
+    #[test]
+    fn duplicate_links_the_copies_to_each_other() {
+        let mut world = World::default();
+
+        let original = world.create_ship(ship(1.0));
+        let engine = world.create_engine(EngineRow::builder().heat(1.0).build());
+        let crew = world.create_crew(CrewRow::builder().rank(1).build());
+
+        {
+            let original = world.allocators.ship.verify(original).unwrap();
+            let engine = world.allocators.engine.verify(engine).unwrap();
+            let crew = world.allocators.crew.verify(crew).unwrap();
+
+            Link::<Ship, Engine>::link(&mut world.state, original, engine, ());
+            Link::<Ship, Crew>::link(&mut world.state, original, crew, ());
+            Link::<Crew, Engine>::link(&mut world.state, crew, engine, ());
+        }
+
+        let copy = world.duplicate_ship(original).unwrap();
+        let copy = world.allocators.ship.verify(copy).unwrap();
+
+        let engine_copy = world.state.ship.engine[copy].unwrap();
+        let crew_copy = world.state.ship.crew[copy].unwrap();
+        let crew_copy = world.allocators.crew.verify(crew_copy).unwrap();
+
+        assert_ne!(engine, engine_copy);
+        assert_eq!(Some(engine_copy), world.state.crew.engine[crew_copy]);
+    }
+}
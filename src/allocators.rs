@@ -1,6 +1,7 @@
 use crate::ids::*;
 use bit_set::BitSet;
 use std::marker::PhantomData;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::ops::{Index, IndexMut};
 use crate::Insert;
@@ -9,6 +10,7 @@ use crate::Insert;
 #[derive(Debug, Clone)]
 pub struct FixedAllocator<T> {
     next_index: usize,
+    capacity: usize,
     marker: PhantomData<T>,
 }
 
@@ -16,17 +18,150 @@ impl<T> Default for FixedAllocator<T> {
     fn default() -> Self {
         Self {
             next_index: 0,
+            capacity: 0,
             marker: PhantomData,
         }
     }
 }
 
 impl<T> FixedAllocator<T> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// A fixed allocator holds no per-entity storage, so its capacity is only a hint that mirrors
+    /// the columns of the arena, keeping the API the same as `GenAllocator`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.capacity = self.capacity.max(self.next_index + additional);
+    }
+
     pub fn create(&mut self) -> Id<T> {
         let id = Id::new(self.next_index);
         self.next_index += 1;
         id
     }
+
+    /// The number of ids created, which matches the length of each column of the arena.
+    pub fn len(&self) -> usize {
+        self.next_index
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next_index == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity.max(self.next_index)
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.capacity = self.next_index;
+    }
+
+    /// Converts an index from `Id::to_usize` back into an id, if it has been created.
+    pub fn resolve(&self, index: usize) -> Option<Id<T>> {
+        if index < self.next_index {
+            Some(Id::new(index))
+        } else {
+            None
+        }
+    }
+
+    /// Converts the `Display` form of an id back into an id, if it has been created.
+    pub fn resolve_str(&self, s: &str) -> Option<Id<T>> {
+        self.resolve(parse_id_index(s)?)
+    }
+
+    /// Hands out the ids that will be created next, without borrowing the allocator.
+    pub fn reservations(&self) -> FixedReservations<T> {
+        FixedReservations {
+            next_index: self.next_index,
+            marker: PhantomData,
+        }
+    }
+
+    /// Creates an id handed out by `FixedReservations`, panicking if it is not the next id.
+    pub fn create_reserved(&mut self, id: Id<T>) -> Id<T> {
+        self.try_create_reserved(id)
+            .unwrap_or_else(|| panic!("{}: reserved ids must be created in order", std::any::type_name::<Self>()))
+    }
+
+    /// Creates an id handed out by `FixedReservations`, or returns `None` if it is not the next id,
+    /// such as when another set of reservations has been created first.
+    pub fn try_create_reserved(&mut self, id: Id<T>) -> Option<Id<T>> {
+        if self.next_index == id.index {
+            Some(self.create())
+        } else {
+            None
+        }
+    }
+
+    /// Reverts the creation of the last id, so that it is created again next.
+    pub fn uncreate(&mut self, id: Id<T>) {
+        assert_eq!(self.next_index, id.index + 1, "{}: only the last id can be uncreated", std::any::type_name::<Self>());
+        self.next_index -= 1;
+    }
+
+    /// Every id created so far.
+    pub fn ids(&self) -> impl Iterator<Item=Id<T>> {
+        (0..self.next_index).map(Id::new)
+    }
+
+    /// The ids present in every column.
+    pub fn filter<'a>(&'a self, columns: &[&'a dyn Presence]) -> impl Iterator<Item=Id<T>> + 'a {
+        present_in_all(columns).map(Id::new)
+    }
+
+    pub fn stats(&self) -> AllocatorStats {
+        AllocatorStats {
+            living: self.next_index,
+            created: self.next_index as u64,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FixedReservations<T> {
+    next_index: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T> FixedReservations<T> {
+    pub fn reserve(&mut self) -> Id<T> {
+        let id = Id::new(self.next_index);
+        self.next_index += 1;
+        id
+    }
+}
+
+/// Slot and lifetime counts of an allocator, for spotting arenas that leak entities.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct AllocatorStats {
+    pub living: usize,
+    /// Slots that are not alive, including retired slots.
+    pub dead: usize,
+    /// Slots whose generation is exhausted, which are never reused.
+    pub retired: usize,
+    /// Dead slots waiting to be reused.
+    pub free: usize,
+    pub max_generation: u32,
+    pub created: u64,
+    pub killed: u64,
+}
+
+/// Allocator counts and approximate column memory of an arena, as returned by the generated `World::stats`.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct ArenaStats {
+    pub allocator: AllocatorStats,
+    pub memory: usize,
 }
 
 #[derive(Debug)]
@@ -34,6 +169,10 @@ pub struct GenAllocator<T> {
     ids: Vec<Valid<T>>,
     dead: Vec<usize>,
     living: BitSet,
+    fresh_gen: Generation,
+    retired: usize,
+    created: u64,
+    killed: u64,
 }
 
 impl<T> Default for GenAllocator<T> {
@@ -42,6 +181,10 @@ impl<T> Default for GenAllocator<T> {
             ids: vec![],
             dead: vec![],
             living: BitSet::new(),
+            fresh_gen: Generation::default(),
+            retired: 0,
+            created: 0,
+            killed: 0,
         }
     }
 }
@@ -51,7 +194,42 @@ impl<T> GenAllocator<T> {
         Default::default()
     }
 
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            ids: Vec::with_capacity(capacity),
+            dead: vec![],
+            living: BitSet::with_capacity(capacity),
+            ..Default::default()
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.ids.reserve(additional);
+        self.living.reserve_len(self.ids.len() + additional);
+    }
+
+    /// The number of slots, living or dead, which matches the length of each column of the arena.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ids.capacity()
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.ids.shrink_to_fit();
+        self.dead.shrink_to_fit();
+        self.living.shrink_to_fit();
+    }
+
     pub fn create(&mut self) -> &Valid<T> {
+        self.created += 1;
+
         if let Some(index) = self.dead.pop() {
             let i = index as usize;
 
@@ -66,7 +244,7 @@ impl<T> GenAllocator<T> {
             &self.ids[i]
         } else {
             let i = self.ids.len();
-            let gen = Generation::default();
+            let gen = self.fresh_gen;
 
             let id = GenId::new(i, gen);
             let id = Valid::new(id);
@@ -78,34 +256,252 @@ impl<T> GenAllocator<T> {
         }
     }
 
-    pub fn verify(&self, id: GenId<T>) -> Option<&Valid<T>> {
+    /// Hands out the ids that will be created next, without borrowing the allocator.
+    pub fn reservations(&self) -> GenReservations<T> {
+        GenReservations {
+            dead: self.dead.iter()
+                .map(|&i| GenId::new(i, self.ids[i].id.gen))
+                .collect(),
+            next_index: self.ids.len(),
+            fresh_gen: self.fresh_gen,
+        }
+    }
+
+    /// Creates an id handed out by `GenReservations`, panicking if its slot has since been taken.
+    pub fn create_reserved(&mut self, id: GenId<T>) -> &Valid<T> {
+        if !self.is_reservable(id) {
+            panic!("{}: reserved id is no longer available", std::any::type_name::<Self>());
+        }
+
+        self.create_reservable(id)
+    }
+
+    /// Creates an id handed out by `GenReservations`, or returns `None` if its slot has since been taken,
+    /// such as by another set of reservations.
+    pub fn try_create_reserved(&mut self, id: GenId<T>) -> Option<&Valid<T>> {
+        if self.is_reservable(id) {
+            Some(self.create_reservable(id))
+        } else {
+            None
+        }
+    }
+
+    /// True if the id is the next fresh slot or a dead slot with the same generation.
+    fn is_reservable(&self, id: GenId<T>) -> bool {
+        let i = id.id.index;
+
+        if i == self.ids.len() {
+            self.fresh_gen == id.gen
+        } else {
+            self.dead.contains(&i) && self.ids[i].id.gen == id.gen
+        }
+    }
+
+    fn create_reservable(&mut self, id: GenId<T>) -> &Valid<T> {
+        let i = id.id.index;
+
+        if i == self.ids.len() {
+            self.ids.push(Valid::new(id));
+        } else {
+            let position = self.dead.iter()
+                .position(|dead| *dead == i)
+                .expect("GenAllocator: reservable slot is missing from the dead list");
+
+            self.dead.remove(position);
+            self.ids[i] = Valid::new(id);
+        }
+
+        self.living.insert(i);
+        self.created += 1;
+
+        &self.ids[i]
+    }
+
+    /// Reverts a create. As with `kill`, the generation is bumped so that `create` cannot hand the same id out again;
+    /// `recreate` brings it back.
+    pub fn uncreate(&mut self, id: GenId<T>) {
+        if self.release(id) {
+            self.created -= 1;
+        }
+    }
+
+    /// Reverts an `uncreate`, bringing back the same id. Panics if the slot has been reused since.
+    pub fn recreate(&mut self, id: GenId<T>) -> &Valid<T> {
+        let index = self.restore(id);
+        self.created += 1;
+
+        &self.ids[index]
+    }
+
+    /// Reverts a kill, bringing back the same id. Panics if the slot has been reused since.
+    pub fn revive(&mut self, id: GenId<T>) -> &Valid<T> {
+        let index = self.restore(id);
+        self.killed -= 1;
+
+        &self.ids[index]
+    }
+
+    /// Frees the slot of a living id, returning false if it was not alive.
+    /// A slot whose generation cannot be incremented is retired instead of reused.
+    fn release(&mut self, id: GenId<T>) -> bool {
+        if !self.is_alive(id) {
+            return false;
+        }
+
         let index = id.id.index;
+        let slot = &mut self.ids[index];
+
+        match slot.id.gen.checked_next() {
+            Some(gen) => {
+                slot.id.gen = gen;
+                self.dead.push(index);
+            },
+            None => self.retired += 1,
+        }
 
-        if let Some(current) = self.ids.get(index) {
-            if id == current.id {
-                return Some(current)
+        self.living.remove(index);
+        true
+    }
+
+    /// Takes back a slot freed by `release`, returning its index.
+    fn restore(&mut self, id: GenId<T>) -> usize {
+        let index = id.id.index;
+
+        assert!(!self.living.contains(index), "{}: cannot restore an id whose slot is in use", std::any::type_name::<Self>());
+
+        let current = self.ids[index].id.gen;
+
+        if current == id.gen {
+            self.retired -= 1;
+        } else {
+            assert_eq!(Some(current), id.gen.checked_next(), "{}: cannot restore an id whose slot has been reused", std::any::type_name::<Self>());
+
+            let position = self.dead.iter()
+                .rposition(|dead| *dead == index)
+                .expect("GenAllocator::restore: dead slot is missing from the dead list");
+            self.dead.remove(position);
+        }
+
+        self.ids[index] = Valid::new(id);
+        self.living.insert(index);
+
+        index
+    }
+
+    /// Moves living entities down to fill dead slots, returning the new id of each living entity.
+    /// Moved entities get a generation newer than any handed out before, so stale ids cannot alias them.
+    pub fn compact(&mut self) -> Remap<T> {
+        let fresh_gen = self.ids.iter()
+            .filter_map(|id| id.id.gen.checked_next())
+            .fold(self.fresh_gen, std::cmp::max);
+
+        let mut ids = Vec::with_capacity(self.living.len());
+        let mut moves = Vec::with_capacity(self.ids.len());
+
+        for (index, old) in self.ids.iter().enumerate() {
+            if self.living.contains(index) {
+                let new = if ids.len() == index {
+                    old.id
+                } else {
+                    GenId::new(ids.len(), fresh_gen)
+                };
+
+                ids.push(Valid::new(new));
+                moves.push(Some((old.id, new)));
+            } else {
+                moves.push(None);
             }
         }
 
-        None
+        self.living = (0..ids.len()).collect();
+        self.ids = ids;
+        self.dead.clear();
+        self.fresh_gen = fresh_gen;
+        self.retired = 0;
+
+        Remap {
+            moves,
+            len: self.ids.len(),
+        }
+    }
+
+    pub fn verify(&self, id: GenId<T>) -> Option<&Valid<T>> {
+        if self.is_alive(id) {
+            Some(&self.ids[id.id.index])
+        } else {
+            None
+        }
+    }
+
+    /// Converts a value from `GenId::to_u64` back into an id, if it is still alive.
+    pub fn resolve(&self, value: u64) -> Option<&Valid<T>> {
+        self.verify(GenId::from_u64(value)?)
+    }
+
+    /// Converts the `Display` form of an id back into an id, if it is still alive.
+    pub fn resolve_str(&self, s: &str) -> Option<&Valid<T>> {
+        self.verify(s.parse().ok()?)
     }
 
     pub fn is_alive(&self, id: GenId<T>) -> bool {
         let index = id.id.index;
         if let Some(current) = self.ids.get(index) {
-            current.id == id
+            current.id == id && self.living.contains(index)
         } else {
             false
         }
     }
 
+    /// Kills the entity. A slot whose generation cannot be incremented is retired instead of reused.
     pub fn kill(&mut self, id: GenId<T>) {
-        if self.is_alive(id) {
-            let id = &mut self.ids[id.id.index];
-            id.id.gen = id.id.gen.next();
+        if self.release(id) {
+            self.killed += 1;
+        }
+    }
+
+    /// Every living id.
+    pub fn ids(&self) -> impl Iterator<Item=&Valid<T>> + '_ {
+        self.living.iter().map(move |index| &self.ids[index])
+    }
+
+    /// The living ids present in every column.
+    pub fn filter<'a>(&'a self, columns: &[&'a dyn Presence]) -> impl Iterator<Item=&'a Valid<T>> + 'a {
+        present_in_all(columns)
+            .filter(move |index| self.living.contains(*index))
+            .map(move |index| &self.ids[index])
+    }
 
-            self.dead.push(id.index());
-            self.living.remove(id.index());
+    pub fn stats(&self) -> AllocatorStats {
+        AllocatorStats {
+            living: self.living.len(),
+            dead: self.ids.len() - self.living.len(),
+            retired: self.retired,
+            free: self.dead.len(),
+            max_generation: self.ids.iter()
+                .map(|id| id.id.gen.value())
+                .max()
+                .unwrap_or(0),
+            created: self.created,
+            killed: self.killed,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GenReservations<T> {
+    dead: Vec<GenId<T>>,
+    next_index: usize,
+    fresh_gen: Generation,
+}
+
+impl<T> GenReservations<T> {
+    pub fn reserve(&mut self) -> GenId<T> {
+        if let Some(id) = self.dead.pop() {
+            id
+        } else {
+            let id = GenId::new(self.next_index, self.fresh_gen);
+            self.next_index += 1;
+            id
         }
     }
 }
@@ -116,8 +512,44 @@ impl<T> Clone for GenAllocator<T> {
             ids: self.ids.iter().map(|id| Valid::new(id.id)).collect(),
             dead: self.dead.clone(),
             living: self.living.clone(),
+            fresh_gen: self.fresh_gen,
+            retired: self.retired,
+            created: self.created,
+            killed: self.killed,
+        }
+    }
+}
+
+/// Maps the ids of a compacted `GenAllocator` to their new values.
+#[derive(Debug)]
+pub struct Remap<T> {
+    moves: Vec<Option<(GenId<T>, GenId<T>)>>,
+    len: usize,
+}
+
+impl<T> Remap<T> {
+    /// Returns the new id of an entity that was alive when the allocator was compacted.
+    pub fn get(&self, id: GenId<T>) -> Option<GenId<T>> {
+        match self.moves.get(id.id.index) {
+            Some(Some((old, new))) if *old == id => Some(*new),
+            _ => None,
         }
     }
+
+    /// The length of the allocator and its columns after compaction.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The old and new index of each living entity, in increasing order.
+    pub fn moves<'a>(&'a self) -> impl Iterator<Item=(usize, usize)> + 'a {
+        self.moves.iter()
+            .filter_map(|m| m.map(|(old, new)| (old.id.index, new.id.index)))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -138,6 +570,46 @@ impl<ID, T> Default for Component<ID, T> {
 impl<'a, ID, T> Component<ID, T> {
     pub fn new() -> Self { Default::default() }
 
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.values.reserve(additional);
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.values.capacity()
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.values.shrink_to_fit();
+    }
+
+    /// The approximate memory used by the values, ignoring any heap data they own.
+    pub fn memory(&self) -> usize {
+        std::mem::size_of::<T>() * self.values.len()
+    }
+
+    /// Moves values to follow a compacted allocator, dropping those of dead entities.
+    pub fn compact(&mut self, remap: &Remap<ID>) {
+        for (old, new) in remap.moves() {
+            self.values.swap(old, new);
+        }
+        self.values.truncate(remap.len());
+    }
+
     fn insert_unchecked(&mut self, id: usize, value: T) {
         match id {
             index if index < self.values.len() => self.values[index] = value,
@@ -160,6 +632,37 @@ impl<'a, ID, T> Component<ID, T> {
     }
 }
 
+impl<ID, T> Component<ID, GenId<T>> {
+    /// Updates links to a compacted arena. Links to entities that were already dead are left as-is.
+    pub fn remap_ids(&mut self, remap: &Remap<T>) {
+        for id in self.values.iter_mut() {
+            if let Some(new) = remap.get(*id) {
+                *id = new;
+            }
+        }
+    }
+}
+
+impl<ID, T> Component<ID, Option<GenId<T>>> {
+    /// Updates links to a compacted arena, clearing links to entities that were already dead.
+    pub fn remap_ids(&mut self, remap: &Remap<T>) {
+        for id in self.values.iter_mut() {
+            *id = id.and_then(|id| remap.get(id));
+        }
+    }
+}
+
+impl<ID, T, E> Component<ID, (GenId<T>, E)> {
+    /// Updates links with edge data to a compacted arena. Links to entities that were already dead are left as-is.
+    pub fn remap_ids(&mut self, remap: &Remap<T>) {
+        for (id, _) in self.values.iter_mut() {
+            if let Some(new) = remap.get(*id) {
+                *id = new;
+            }
+        }
+    }
+}
+
 impl<ID, T> Index<&Id<ID>> for Component<ID, T> {
     type Output = T;
 
@@ -200,58 +703,496 @@ impl<ID, T> Insert<Valid<ID>, T> for Component<ID, T> {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::allocators::{FixedAllocator, GenAllocator};
-    use crate::Generation;
+/// Implemented by the columns that can be used to filter entities by presence: sparse components and tags.
+pub trait Presence {
+    /// Every index that may be present, which can include indices that are no longer present.
+    fn candidates(&self) -> &BitSet;
 
+    fn is_present(&self, index: usize) -> bool;
+}
 
-    #[derive(Debug, Default, Clone)]
-    struct Fixed;
+/// The indices present in every column, scanning the column with the fewest candidates.
+fn present_in_all<'a>(columns: &[&'a dyn Presence]) -> impl Iterator<Item=usize> + 'a {
+    let mut columns = columns.to_vec();
+    columns.sort_by_key(|c| c.candidates().len());
 
-    #[derive(Debug, Default, Clone)]
-    struct Gen;
+    let (first, rest) = columns.split_first()
+        .map(|(first, rest)| (*first, rest.to_vec()))
+        .expect("present_in_all: at least one column is required");
 
-    #[test]
-    #[should_panic]
-    fn insert_given_invalid_id_panics() {
-        let mut allocator = FixedAllocator::<Fixed>::default();
-        let mut component = Component::<Fixed, u32>::new();
+    first.candidates()
+        .iter()
+        .filter(move |index| first.is_present(*index) && rest.iter().all(|c| c.is_present(*index)))
+}
 
-        let _id0 = allocator.create();
-        let id1 = allocator.create();
+/// A column of optional values, with a bitset tracking which entities have a value.
+#[derive(Debug, Clone)]
+pub struct Sparse<ID, T> {
+    values: Vec<Option<T>>,
+    present: BitSet,
+    marker: PhantomData<ID>,
+}
 
-        component.insert(&id1, 0);
+impl<ID, T> Default for Sparse<ID, T> {
+    fn default() -> Self {
+        Self {
+            values: vec![],
+            present: BitSet::new(),
+            marker: PhantomData,
+        }
     }
+}
 
-    #[test]
-    fn insert_and_retrieve() {
-        let mut allocator = FixedAllocator::<Fixed>::default();
-        let mut component = Component::<Fixed, u32>::new();
+impl<ID, T> Sparse<ID, T> {
+    pub fn new() -> Self { Default::default() }
 
-        let id = allocator.create();
-        component.insert(&id, 3);
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            present: BitSet::with_capacity(capacity),
+            marker: PhantomData,
+        }
+    }
 
-        assert_eq!(&3, component.get_unchecked(id.index));
+    pub fn reserve(&mut self, additional: usize) {
+        self.values.reserve(additional);
+        self.present.reserve_len(self.values.len() + additional);
     }
 
-    #[test]
-    fn reuse_index() {
-        let mut allocator = GenAllocator::<Gen>::default();
-        let mut component = Component::<Gen, u32>::new();
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
 
-        let id_0_1 = allocator.create();
-        component.insert(id_0_1, 2);
-        let id_0_1 = id_0_1.id;
-        allocator.kill(id_0_1);
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
 
-        let id_0_2 = allocator.create();
-        component.insert(id_0_2, 3);
+    pub fn capacity(&self) -> usize {
+        self.values.capacity()
+    }
 
-        assert_eq!(id_0_1.id.index, id_0_2.id.id.index); // same index
-        assert_ne!(id_0_1.gen, id_0_2.id.gen); // different gen
-        assert_eq!(&3, component.get_unchecked(id_0_2.index()));
+    pub fn shrink_to_fit(&mut self) {
+        self.values.shrink_to_fit();
+        self.present.shrink_to_fit();
+    }
+
+    /// The approximate memory used by the values, ignoring any heap data they own.
+    pub fn memory(&self) -> usize {
+        std::mem::size_of::<Option<T>>() * self.values.len()
+    }
+
+    pub fn get<I: ValidId<ID>>(&self, id: &I) -> Option<&T> {
+        self[id].as_ref()
+    }
+
+    /// Moves values to follow a compacted allocator, dropping those of dead entities.
+    pub fn compact(&mut self, remap: &Remap<ID>) {
+        for (old, new) in remap.moves() {
+            self.values.swap(old, new);
+        }
+        self.values.truncate(remap.len());
+
+        self.present = self.values.iter()
+            .enumerate()
+            .filter(|(_, value)| value.is_some())
+            .map(|(index, _)| index)
+            .collect();
+    }
+
+    fn insert_unchecked(&mut self, index: usize, value: Option<T>) {
+        if value.is_some() {
+            self.present.insert(index);
+        } else {
+            self.present.remove(index);
+        }
+
+        match index {
+            index if index < self.values.len() => self.values[index] = value,
+            index if index == self.values.len() => self.values.push(value),
+            _ => { panic!("{}: invalid index, confirm that Arena::insert() is configured properly", std::any::type_name::<Self>()) }
+        }
+    }
+}
+
+impl<ID, T> Sparse<ID, GenId<T>> {
+    /// Updates links to a compacted arena, clearing links to entities that were already dead.
+    pub fn remap_ids(&mut self, remap: &Remap<T>) {
+        for (index, id) in self.values.iter_mut().enumerate() {
+            *id = id.and_then(|id| remap.get(id));
+
+            if id.is_none() {
+                self.present.remove(index);
+            }
+        }
+    }
+}
+
+impl<ID, T, E> Sparse<ID, (GenId<T>, E)> {
+    /// Updates links with edge data to a compacted arena, clearing links to entities that were already dead.
+    pub fn remap_ids(&mut self, remap: &Remap<T>) {
+        for (index, link) in self.values.iter_mut().enumerate() {
+            if let Some(new) = link.as_ref().and_then(|(id, _)| remap.get(*id)) {
+                if let Some((id, _)) = link {
+                    *id = new;
+                }
+            } else {
+                *link = None;
+                self.present.remove(index);
+            }
+        }
+    }
+}
+
+impl<ID, T> Presence for Sparse<ID, T> {
+    fn candidates(&self) -> &BitSet {
+        &self.present
+    }
+
+    fn is_present(&self, index: usize) -> bool {
+        matches!(self.values.get(index), Some(Some(_)))
+    }
+}
+
+impl<ID, T, I: ValidId<ID>> Index<&I> for Sparse<ID, T> {
+    type Output = Option<T>;
+
+    fn index(&self, index: &I) -> &Self::Output {
+        self.values
+            .get(index.index())
+            .expect(&format!("{}: invalid index, confirm that Arena::insert() is configured properly", std::any::type_name::<Self>()))
+    }
+}
+
+/// Marks the entity as present, since the value may be set through the reference.
+impl<ID, T, I: ValidId<ID>> IndexMut<&I> for Sparse<ID, T> {
+    fn index_mut(&mut self, index: &I) -> &mut Self::Output {
+        let index = index.index();
+        self.present.insert(index);
+
+        self.values
+            .get_mut(index)
+            .expect(&format!("{}: invalid index, confirm that Arena::insert() is configured properly", std::any::type_name::<Self>()))
+    }
+}
+
+impl<ID, T, I: ValidId<ID>> Insert<I, Option<T>> for Sparse<ID, T> {
+    fn insert(&mut self, id: &I, value: Option<T>) {
+        self.insert_unchecked(id.index(), value);
+    }
+}
+
+/// A column of markers stored as a bitset. Tagging an entity is the same as inserting `true`.
+#[derive(Debug, Clone)]
+pub struct Tag<ID> {
+    bits: BitSet,
+    marker: PhantomData<ID>,
+}
+
+impl<ID> Default for Tag<ID> {
+    fn default() -> Self {
+        Self {
+            bits: BitSet::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<ID> Tag<ID> {
+    pub fn new() -> Self { Default::default() }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bits: BitSet::with_capacity(capacity),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        let len = self.bits.get_ref().len();
+        self.bits.reserve_len(len + additional);
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.bits.shrink_to_fit();
+    }
+
+    /// The approximate memory used by the bits.
+    pub fn memory(&self) -> usize {
+        self.bits.get_ref().storage().len() * std::mem::size_of::<u32>()
+    }
+
+    pub fn set<I: ValidId<ID>>(&mut self, id: &I) {
+        self.bits.insert(id.index());
+    }
+
+    pub fn clear<I: ValidId<ID>>(&mut self, id: &I) {
+        self.bits.remove(id.index());
+    }
+
+    pub fn contains<I: ValidId<ID>>(&self, id: &I) -> bool {
+        self.bits.contains(id.index())
+    }
+
+    /// The number of tagged entities.
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// The indices of the tagged entities, for combining with other columns.
+    pub fn bits(&self) -> &BitSet {
+        &self.bits
+    }
+
+    /// The tagged entities of a fixed arena.
+    pub fn ids<'a>(&'a self) -> impl Iterator<Item=Id<ID>> + 'a {
+        self.bits.iter().map(Id::new)
+    }
+
+    /// The tagged entities of a generational arena that are still alive.
+    pub fn valid_ids<'a>(&'a self, allocator: &'a GenAllocator<ID>) -> impl Iterator<Item=&'a Valid<ID>> + 'a {
+        self.bits.iter()
+            .filter(move |index| allocator.living.contains(*index))
+            .map(move |index| &allocator.ids[index])
+    }
+
+    /// Moves tags to follow a compacted allocator, dropping those of dead entities.
+    pub fn compact(&mut self, remap: &Remap<ID>) {
+        let bits = remap.moves()
+            .filter(|(old, _)| self.bits.contains(*old))
+            .map(|(_, new)| new)
+            .collect();
+
+        self.bits = bits;
+    }
+}
+
+impl<ID> Presence for Tag<ID> {
+    fn candidates(&self) -> &BitSet {
+        &self.bits
+    }
+
+    fn is_present(&self, index: usize) -> bool {
+        self.bits.contains(index)
+    }
+}
+
+impl<ID, I: ValidId<ID>> Index<&I> for Tag<ID> {
+    type Output = bool;
+
+    fn index(&self, index: &I) -> &Self::Output {
+        if self.contains(index) {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+impl<ID, I: ValidId<ID>> Insert<I, bool> for Tag<ID> {
+    fn insert(&mut self, id: &I, value: bool) {
+        if value {
+            self.set(id);
+        } else {
+            self.clear(id);
+        }
+    }
+}
+
+/// A column keeping the last `N` values of each entity, oldest first.
+/// Indexing and `Insert` act on the latest value, so the column can be used like a `Component`.
+#[derive(Debug, Clone)]
+pub struct History<ID, T, const N: usize> {
+    values: Vec<VecDeque<T>>,
+    marker: PhantomData<ID>,
+}
+
+impl<ID, T, const N: usize> Default for History<ID, T, N> {
+    fn default() -> Self {
+        Self {
+            values: vec![],
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<ID, T, const N: usize> History<ID, T, N> {
+    pub fn new() -> Self { Default::default() }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.values.reserve(additional);
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.values.capacity()
+    }
+
+    pub fn shrink_to_fit(&mut self) {
+        self.values.shrink_to_fit();
+    }
+
+    /// The approximate memory used by full histories, ignoring any heap data the values own.
+    pub fn memory(&self) -> usize {
+        std::mem::size_of::<T>() * N * self.values.len()
+    }
+
+    /// Adds a value, dropping the oldest once `N` values are kept.
+    pub fn push<I: ValidId<ID>>(&mut self, id: &I, value: T) {
+        let history = self.get_history_mut(id.index());
+
+        if history.len() == N {
+            history.pop_front();
+        }
+        history.push_back(value);
+    }
+
+    pub fn latest<I: ValidId<ID>>(&self, id: &I) -> &T {
+        self.get_history(id.index())
+            .back()
+            .expect("History: every entity has at least one value")
+    }
+
+    /// The kept values of the entity, oldest first.
+    pub fn iter_history<I: ValidId<ID>>(&self, id: &I) -> impl DoubleEndedIterator<Item=&T> {
+        self.get_history(id.index()).iter()
+    }
+
+    /// Replaces the kept values of the entity, oldest first, keeping the newest `N`. Panics if there are none.
+    pub fn set_history<I: ValidId<ID>>(&mut self, id: &I, values: impl IntoIterator<Item=T>) {
+        let history = self.get_history_mut(id.index());
+        history.clear();
+
+        for value in values {
+            if history.len() == N {
+                history.pop_front();
+            }
+            history.push_back(value);
+        }
+
+        assert!(!history.is_empty(), "History: every entity has at least one value");
+    }
+
+    /// Moves histories to follow a compacted allocator, dropping those of dead entities.
+    pub fn compact(&mut self, remap: &Remap<ID>) {
+        for (old, new) in remap.moves() {
+            self.values.swap(old, new);
+        }
+        self.values.truncate(remap.len());
+    }
+
+    fn get_history(&self, index: usize) -> &VecDeque<T> {
+        self.values
+            .get(index)
+            .expect(&format!("{}: invalid index, confirm that Arena::insert() is configured properly", std::any::type_name::<Self>()))
+    }
+
+    fn get_history_mut(&mut self, index: usize) -> &mut VecDeque<T> {
+        self.values
+            .get_mut(index)
+            .expect(&format!("{}: invalid index, confirm that Arena::insert() is configured properly", std::any::type_name::<Self>()))
+    }
+}
+
+impl<ID, T, I: ValidId<ID>, const N: usize> Index<&I> for History<ID, T, N> {
+    type Output = T;
+
+    fn index(&self, index: &I) -> &Self::Output {
+        self.latest(index)
+    }
+}
+
+impl<ID, T, I: ValidId<ID>, const N: usize> IndexMut<&I> for History<ID, T, N> {
+    fn index_mut(&mut self, index: &I) -> &mut Self::Output {
+        self.get_history_mut(index.index())
+            .back_mut()
+            .expect("History: every entity has at least one value")
+    }
+}
+
+/// Starts a new history holding only the value.
+impl<ID, T, I: ValidId<ID>, const N: usize> Insert<I, T> for History<ID, T, N> {
+    fn insert(&mut self, id: &I, value: T) {
+        let mut history = VecDeque::with_capacity(N);
+        history.push_back(value);
+
+        match id.index() {
+            index if index < self.values.len() => self.values[index] = history,
+            index if index == self.values.len() => self.values.push(history),
+            _ => { panic!("{}: invalid index, confirm that Arena::insert() is configured properly", std::any::type_name::<Self>()) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocators::{FixedAllocator, GenAllocator};
+    use crate::Generation;
+
+
+    #[derive(Debug, Default, Clone)]
+    struct Fixed;
+
+    #[derive(Debug, Default, Clone)]
+    struct Gen;
+
+    #[test]
+    #[should_panic]
+    fn insert_given_invalid_id_panics() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut component = Component::<Fixed, u32>::new();
+
+        let _id0 = allocator.create();
+        let id1 = allocator.create();
+
+        component.insert(&id1, 0);
+    }
+
+    #[test]
+    fn insert_and_retrieve() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut component = Component::<Fixed, u32>::new();
+
+        let id = allocator.create();
+        component.insert(&id, 3);
+
+        assert_eq!(&3, component.get_unchecked(id.index));
+    }
+
+    #[test]
+    fn reuse_index() {
+        let mut allocator = GenAllocator::<Gen>::default();
+        let mut component = Component::<Gen, u32>::new();
+
+        let id_0_1 = allocator.create();
+        component.insert(id_0_1, 2);
+        let id_0_1 = id_0_1.id;
+        allocator.kill(id_0_1);
+
+        let id_0_2 = allocator.create();
+        component.insert(id_0_2, 3);
+
+        assert_eq!(id_0_1.id.index, id_0_2.id.id.index); // same index
+        assert_ne!(id_0_1.gen, id_0_2.id.gen); // different gen
+        assert_eq!(&3, component.get_unchecked(id_0_2.index()));
     }
 
     #[test]
@@ -340,4 +1281,559 @@ mod tests {
 
         assert_eq!(id_0_gen_2, GenId::new(0, Generation::default().next()));
     }
+
+    #[test]
+    fn fixed_reservations_match_created_ids() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let _id0 = allocator.create();
+
+        let mut reservations = allocator.reservations();
+        let id1 = reservations.reserve();
+        let id2 = reservations.reserve();
+
+        assert_eq!(id1, allocator.create_reserved(id1));
+        assert_eq!(id2, allocator.create_reserved(id2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed_create_reserved_out_of_order_panics() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+
+        let mut reservations = allocator.reservations();
+        let _id0 = reservations.reserve();
+        let id1 = reservations.reserve();
+
+        allocator.create_reserved(id1);
+    }
+
+    #[test]
+    fn try_create_reserved_rejects_ids_reserved_twice() {
+        let mut fixed = FixedAllocator::<Fixed>::default();
+        let fixed_id = fixed.reservations().reserve();
+        let fixed_again = fixed.reservations().reserve();
+
+        let mut gen = GenAllocator::<Test>::default();
+        let gen_id = gen.reservations().reserve();
+        let gen_again = gen.reservations().reserve();
+
+        assert_eq!(Some(fixed_id), fixed.try_create_reserved(fixed_id));
+        assert_eq!(None, fixed.try_create_reserved(fixed_again));
+        assert_eq!(Some(gen_id), gen.try_create_reserved(gen_id).map(|id| id.id));
+        assert!(gen.try_create_reserved(gen_again).is_none());
+    }
+
+    #[test]
+    fn gen_reservations_reuse_dead_slots() {
+        let mut allocator = GenAllocator::<Test>::default();
+
+        let id_0_gen_1 = allocator.create().id;
+        allocator.kill(id_0_gen_1);
+
+        let mut reservations = allocator.reservations();
+        let id_0_gen_2 = reservations.reserve();
+        let id_1_gen_1 = reservations.reserve();
+
+        assert_eq!(GenId::new(0, Generation::default().next()), id_0_gen_2);
+        assert_eq!(GenId::new(1, Generation::default()), id_1_gen_1);
+
+        assert_eq!(id_0_gen_2, allocator.create_reserved(id_0_gen_2).id);
+        assert_eq!(id_1_gen_1, allocator.create_reserved(id_1_gen_1).id);
+        assert!(allocator.is_alive(id_0_gen_2));
+        assert!(allocator.is_alive(id_1_gen_1));
+    }
+
+    #[test]
+    fn gen_create_reserved_after_later_kill() {
+        let mut allocator = GenAllocator::<Test>::default();
+
+        let id_0_gen_1 = allocator.create().id;
+
+        let mut reservations = allocator.reservations();
+        let id_1_gen_1 = reservations.reserve();
+
+        allocator.kill(id_0_gen_1);
+
+        assert_eq!(id_1_gen_1, allocator.create_reserved(id_1_gen_1).id);
+        assert_eq!(GenId::new(0, Generation::default().next()), allocator.create().id);
+    }
+
+    #[test]
+    fn component_reserve_and_len() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut component = Component::<Fixed, u32>::with_capacity(4);
+
+        assert!(component.capacity() >= 4);
+
+        component.reserve(16);
+        component.insert(&allocator.create(), 1);
+
+        assert_eq!(1, component.len());
+        assert_eq!(1, allocator.len());
+        assert!(component.capacity() >= 17);
+
+        component.shrink_to_fit();
+
+        assert!(component.capacity() >= 1);
+    }
+
+    #[test]
+    fn fixed_allocator_capacity_hint() {
+        let mut allocator = FixedAllocator::<Fixed>::with_capacity(2);
+        assert_eq!(2, allocator.capacity());
+
+        allocator.reserve(4);
+        allocator.create();
+        assert_eq!(4, allocator.capacity());
+        assert_eq!(1, allocator.len());
+
+        allocator.shrink_to_fit();
+        assert_eq!(1, allocator.capacity());
+    }
+
+    #[test]
+    fn gen_allocator_len_counts_dead_slots() {
+        let mut allocator = GenAllocator::<Test>::with_capacity(8);
+
+        let id = allocator.create().id;
+        allocator.create();
+        allocator.kill(id);
+
+        assert_eq!(2, allocator.len());
+        assert!(allocator.capacity() >= 8);
+    }
+
+    #[test]
+    fn compact_moves_living_entities_down() {
+        let mut allocator = GenAllocator::<Test>::default();
+        let mut component = Component::<Test, u32>::new();
+
+        let ids: Vec<_> = (0..4)
+            .map(|i| {
+                let id = allocator.create();
+                component.insert(id, i);
+                id.id
+            })
+            .collect();
+
+        allocator.kill(ids[0]);
+        allocator.kill(ids[2]);
+
+        let remap = allocator.compact();
+        component.compact(&remap);
+
+        let new_1 = remap.get(ids[1]).unwrap();
+        let new_3 = remap.get(ids[3]).unwrap();
+
+        assert_eq!(2, remap.len());
+        assert_eq!(None, remap.get(ids[0]));
+        assert_eq!(0, new_1.id.index);
+        assert_eq!(1, new_3.id.index);
+
+        assert!(allocator.is_alive(new_1));
+        assert!(allocator.is_alive(new_3));
+        assert!(!allocator.is_alive(ids[1]));
+
+        assert_eq!(vec![1, 3], component.values);
+    }
+
+    #[test]
+    fn compact_does_not_alias_stale_ids() {
+        let mut allocator = GenAllocator::<Test>::default();
+
+        let id0 = allocator.create().id;
+        let id1 = allocator.create().id;
+        let id2 = allocator.create().id;
+        allocator.kill(id0);
+        allocator.kill(id2);
+
+        let remap = allocator.compact();
+        let new = allocator.create().id;
+
+        assert!(allocator.is_alive(remap.get(id1).unwrap()));
+        assert!(!allocator.is_alive(id0));
+        assert!(!allocator.is_alive(id1));
+        assert_eq!(1, new.id.index);
+        assert_ne!(id1.gen, new.gen);
+    }
+
+    #[test]
+    fn remap_ids_updates_links() {
+        let mut allocator = GenAllocator::<Test>::default();
+        let mut required = Component::<Fixed, GenId<Test>>::new();
+        let mut optional = Component::<Fixed, Option<GenId<Test>>>::new();
+        let mut fixed = FixedAllocator::<Fixed>::default();
+
+        let id0 = allocator.create().id;
+        let id1 = allocator.create().id;
+
+        let a = fixed.create();
+        required.insert(&a, id1);
+        optional.insert(&a, Some(id0));
+
+        allocator.kill(id0);
+        let remap = allocator.compact();
+
+        required.remap_ids(&remap);
+        optional.remap_ids(&remap);
+
+        assert_eq!(remap.get(id1).unwrap(), required[&a]);
+        assert_eq!(None, optional[&a]);
+    }
+
+    #[test]
+    fn remap_ids_keeps_edge_data() {
+        let mut allocator = GenAllocator::<Test>::default();
+        let mut required = Component::<Fixed, (GenId<Test>, u32)>::new();
+        let mut optional = Sparse::<Fixed, (GenId<Test>, u32)>::new();
+        let mut fixed = FixedAllocator::<Fixed>::default();
+
+        let id0 = allocator.create().id;
+        let id1 = allocator.create().id;
+
+        let a = fixed.create();
+        required.insert(&a, (id1, 7));
+        optional.insert(&a, Some((id0, 3)));
+
+        allocator.kill(id0);
+        let remap = allocator.compact();
+
+        required.remap_ids(&remap);
+        optional.remap_ids(&remap);
+
+        assert_eq!((remap.get(id1).unwrap(), 7), required[&a]);
+        assert_eq!(None, optional[&a]);
+    }
+
+    #[test]
+    fn fixed_resolve_checks_bounds() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+
+        let id = allocator.create();
+
+        assert_eq!(Some(id), allocator.resolve(id.to_usize()));
+        assert_eq!(Some(id), allocator.resolve_str(&id.to_string()));
+        assert_eq!(None, allocator.resolve(1));
+        assert_eq!(None, allocator.resolve_str("Id(1)"));
+        assert_eq!(None, allocator.resolve_str("Id(x)"));
+    }
+
+    #[test]
+    fn gen_resolve_round_trips_living_ids() {
+        let mut allocator = GenAllocator::<Test>::default();
+
+        let id = allocator.create().id;
+        let packed = id.to_u64().unwrap();
+        let text = id.to_string();
+
+        assert_eq!(Some(id), allocator.resolve(packed).map(Valid::id));
+        assert_eq!(Some(id), allocator.resolve_str(&text).map(Valid::id));
+
+        allocator.kill(id);
+
+        assert!(allocator.resolve(packed).is_none());
+        assert!(allocator.resolve_str(&text).is_none());
+    }
+
+    #[test]
+    fn gen_id_from_u64_rejects_zero_generation() {
+        assert!(GenId::<Test>::from_u64(3).is_none());
+        assert_eq!(Some(GenId::new(3, Generation::default())), GenId::<Test>::from_u64(1 << 32 | 3));
+    }
+
+    #[test]
+    fn gen_id_from_str() {
+        assert_eq!(Ok(GenId::new(3, Generation::default().next())), "GenId(3,2)".parse::<GenId<Test>>());
+        assert!("GenId(3,0)".parse::<GenId<Test>>().is_err());
+        assert!("GenId(3)".parse::<GenId<Test>>().is_err());
+        assert!("Id(3)".parse::<GenId<Test>>().is_err());
+    }
+
+    #[test]
+    fn gen_stats_count_churn() {
+        let mut allocator = GenAllocator::<Gen>::default();
+
+        let a = allocator.create().id;
+        let _b = allocator.create().id;
+        allocator.kill(a);
+        let _c = allocator.create().id;
+        let d = allocator.create().id;
+        allocator.kill(d);
+
+        let stats = allocator.stats();
+
+        assert_eq!(2, stats.living);
+        assert_eq!(1, stats.dead);
+        assert_eq!(1, stats.free);
+        assert_eq!(0, stats.retired);
+        assert_eq!(2, stats.max_generation);
+        assert_eq!(4, stats.created);
+        assert_eq!(2, stats.killed);
+    }
+
+    #[test]
+    fn kill_retires_exhausted_slot() {
+        let mut allocator = GenAllocator::<Gen>::default();
+
+        let id = allocator.create().id;
+        let exhausted = GenId::<Gen>::from_u64(u64::from(u32::MAX) << 32).unwrap().gen;
+        allocator.ids[id.id.index].id.gen = exhausted;
+        let id = allocator.ids[id.id.index].id;
+
+        allocator.kill(id);
+
+        assert!(!allocator.is_alive(id));
+        assert_eq!(1, allocator.stats().retired);
+        assert_eq!(0, allocator.stats().free);
+        assert_ne!(id.id.index, allocator.create().index());
+    }
+
+    #[test]
+    fn component_memory() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut component = Component::<Fixed, u32>::new();
+
+        component.insert(&allocator.create(), 1);
+        component.insert(&allocator.create(), 2);
+
+        assert_eq!(8, component.memory());
+    }
+
+    #[test]
+    fn tag_set_and_clear() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut tag = Tag::<Fixed>::new();
+
+        let a = allocator.create();
+        let b = allocator.create();
+
+        tag.set(&a);
+        tag.insert(&b, true);
+        tag.clear(&a);
+
+        assert!(!tag.contains(&a));
+        assert!(tag[&b]);
+        assert_eq!(vec![b], tag.ids().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn tag_valid_ids_skips_dead() {
+        let mut allocator = GenAllocator::<Gen>::default();
+        let mut tag = Tag::<Gen>::new();
+
+        let a = allocator.create().id;
+        let b = allocator.create().id;
+
+        tag.set(allocator.verify(a).unwrap());
+        tag.set(allocator.verify(b).unwrap());
+        allocator.kill(a);
+
+        assert_eq!(vec![b], tag.valid_ids(&allocator).map(Valid::id).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn tag_compact() {
+        let mut allocator = GenAllocator::<Gen>::default();
+        let mut tag = Tag::<Gen>::new();
+
+        let a = allocator.create().id;
+        let b = allocator.create().id;
+        tag.set(allocator.verify(b).unwrap());
+        allocator.kill(a);
+
+        let remap = allocator.compact();
+        tag.compact(&remap);
+
+        let b = remap.get(b).unwrap();
+        assert!(tag.contains(allocator.verify(b).unwrap()));
+        assert_eq!(1, tag.len());
+    }
+
+    #[test]
+    fn sparse_tracks_presence_on_insert() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut sparse = Sparse::<Fixed, u32>::new();
+
+        let a = allocator.create();
+        let b = allocator.create();
+
+        sparse.insert(&a, Some(1));
+        sparse.insert(&b, None);
+
+        assert!(sparse.is_present(a.index));
+        assert!(!sparse.is_present(b.index));
+        assert_eq!(Some(&1), sparse.get(&a));
+
+        sparse.insert(&a, None);
+
+        assert!(sparse.candidates().is_empty());
+    }
+
+    #[test]
+    fn filter_requires_every_column() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut sparse = Sparse::<Fixed, u32>::new();
+        let mut tag = Tag::<Fixed>::new();
+
+        for i in 0..4 {
+            let id = allocator.create();
+            sparse.insert(&id, if i % 2 == 0 { Some(i) } else { None });
+            tag.insert(&id, i < 3);
+        }
+
+        let ids: Vec<_> = allocator.filter(&[&sparse, &tag])
+            .map(Id::to_usize)
+            .collect();
+
+        assert_eq!(vec![0, 2], ids);
+    }
+
+    #[test]
+    fn filter_skips_values_cleared_through_index_mut() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut sparse = Sparse::<Fixed, u32>::new();
+
+        let a = allocator.create();
+        sparse.insert(&a, Some(1));
+        sparse[&a] = None;
+
+        assert_eq!(0, allocator.filter(&[&sparse]).count());
+
+        sparse[&a] = Some(2);
+
+        assert_eq!(1, allocator.filter(&[&sparse]).count());
+    }
+
+    #[test]
+    fn gen_filter_skips_dead() {
+        let mut allocator = GenAllocator::<Gen>::default();
+        let mut tag = Tag::<Gen>::new();
+
+        let a = allocator.create().id;
+        let b = allocator.create().id;
+        tag.set(allocator.verify(a).unwrap());
+        tag.set(allocator.verify(b).unwrap());
+        allocator.kill(a);
+
+        let ids: Vec<_> = allocator.filter(&[&tag]).map(Valid::id).collect();
+
+        assert_eq!(vec![b], ids);
+    }
+
+    #[test]
+    fn gen_ids_skips_dead() {
+        let mut allocator = GenAllocator::<Gen>::default();
+
+        let a = allocator.create().id;
+        let b = allocator.create().id;
+        allocator.kill(a);
+
+        let ids: Vec<_> = allocator.ids().map(Valid::id).collect();
+
+        assert_eq!(vec![b], ids);
+    }
+
+    #[test]
+    fn history_keeps_last_values() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut history = History::<Fixed, u32, 3>::new();
+
+        let id = allocator.create();
+        history.insert(&id, 0);
+
+        for i in 1..5 {
+            history.push(&id, i);
+        }
+
+        assert_eq!(&4, history.latest(&id));
+        assert_eq!(vec![&2, &3, &4], history.iter_history(&id).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn history_set_keeps_newest() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut history = History::<Fixed, u32, 2>::new();
+
+        let id = allocator.create();
+        history.insert(&id, 0);
+        history.set_history(&id, vec![1, 2, 3]);
+
+        assert_eq!(vec![&2, &3], history.iter_history(&id).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn history_insert_restarts() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+        let mut history = History::<Fixed, u32, 3>::new();
+
+        let id = allocator.create();
+        history.insert(&id, 0);
+        history.push(&id, 1);
+        history.insert(&id, 5);
+
+        assert_eq!(vec![&5], history.iter_history(&id).collect::<Vec<_>>());
+        assert_eq!(5, history[&id]);
+    }
+
+    #[test]
+    fn fixed_uncreate_recreates_same_id() {
+        let mut allocator = FixedAllocator::<Fixed>::default();
+
+        allocator.create();
+        let id = allocator.create();
+        allocator.uncreate(id);
+
+        assert_eq!(id, allocator.create_reserved(id));
+    }
+
+    #[test]
+    fn gen_uncreate_and_recreate() {
+        let mut allocator = GenAllocator::<Gen>::default();
+
+        let id = allocator.create().id;
+        allocator.uncreate(id);
+
+        assert!(!allocator.is_alive(id));
+        assert_eq!(0, allocator.stats().created);
+        assert_eq!(id, allocator.recreate(id).id());
+        assert_eq!(1, allocator.stats().created);
+        assert_eq!(0, allocator.stats().free);
+    }
+
+    #[test]
+    fn gen_create_after_uncreate_gives_new_id() {
+        let mut allocator = GenAllocator::<Gen>::default();
+
+        let id = allocator.create().id;
+        allocator.uncreate(id);
+        let next = allocator.create().id;
+
+        assert_ne!(id, next);
+        assert!(!allocator.is_alive(id));
+    }
+
+    #[test]
+    fn gen_revive_restores_killed_id() {
+        let mut allocator = GenAllocator::<Gen>::default();
+
+        let id = allocator.create().id;
+        allocator.kill(id);
+
+        assert_eq!(id, allocator.revive(id).id());
+        assert!(allocator.is_alive(id));
+        assert_eq!(0, allocator.stats().free);
+        assert_eq!(0, allocator.stats().killed);
+    }
+
+    #[test]
+    #[should_panic]
+    fn gen_revive_panics_when_slot_reused() {
+        let mut allocator = GenAllocator::<Gen>::default();
+
+        let id = allocator.create().id;
+        allocator.kill(id);
+        allocator.create();
+
+        allocator.revive(id);
+    }
 }
\ No newline at end of file
@@ -12,6 +12,12 @@ pub struct Arena {
     pub default_components: Vec<ComponentType>,
     pub references: HashMap<CamelCase, LinkType>,
     pub ownership: HashMap<CamelCase, LinkType>,
+    pub edges: HashMap<CamelCase, Type>,
+    pub hierarchy: bool,
+    pub filters: Vec<Filter>,
+    pub events: bool,
+    pub templates: Vec<ArenaTemplate>,
+    pub splits: Vec<ColumnSplit>,
 }
 
 //	From	    To	        Relationsh	Use Case	                                        Example
@@ -57,6 +63,12 @@ impl Arena {
             default_components: Default::default(),
             references: Default::default(),
             ownership: Default::default(),
+            edges: Default::default(),
+            hierarchy: false,
+            filters: vec![],
+            events: false,
+            templates: vec![],
+            splits: vec![],
         }
     }
 
@@ -68,6 +80,12 @@ impl Arena {
             default_components: Default::default(),
             references: Default::default(),
             ownership: Default::default(),
+            edges: Default::default(),
+            hierarchy: false,
+            filters: vec![],
+            events: false,
+            templates: vec![],
+            splits: vec![],
         }
     }
 
@@ -81,6 +99,39 @@ impl Arena {
         self
     }
 
+    /// Adds the template's components, and implements its trait for the arena struct if it has one.
+    pub fn apply_template(mut self, template: &ArenaTemplate) -> Self {
+        self.components.extend(template.components.iter().cloned());
+        self.default_components.extend(template.default_components.iter().cloned());
+        self.templates.push(template.clone());
+        self
+    }
+
+    /// Entities of the arena form a forest, with parent and children columns kept consistent.
+    pub fn add_hierarchy(mut self) -> Self {
+        self.hierarchy = true;
+        self
+    }
+
+    /// Adds an `events` queue that `create`, `kill` and links push `Event`s into, for systems to drain.
+    pub fn add_events(mut self) -> Self {
+        self.events = true;
+        self
+    }
+
+    /// Generates `World::iter_{arena}_{name}`, yielding the entities that have every listed sparse component or tag.
+    pub fn add_filter(mut self, name: &str, columns: &[&str]) -> Self {
+        self.filters.push(Filter::new(name, columns));
+        self
+    }
+
+    /// Generates `split_{name}`, borrowing the `reads` columns as shared and the `writes` columns as mutable,
+    /// so that systems can hold disjoint parts of the arena at the same time.
+    pub fn add_split(mut self, name: &str, reads: &[&str], writes: &[&str]) -> Self {
+        self.splits.push(ColumnSplit::new(name, reads, writes));
+        self
+    }
+
     pub fn add_reference(&mut self, reference_to: &Arena, link_type: LinkType) {
         self.references.insert(reference_to.name.clone(), link_type);
     }
@@ -89,17 +140,34 @@ impl Arena {
         self.ownership.insert(owned.name.clone(), link_type);
     }
 
+    /// Adds a reference whose column stores `(Id, edge)`. The edge type must implement Clone.
+    pub fn add_reference_with_edge(&mut self, reference_to: &Arena, link_type: LinkType, edge: &str) {
+        self.add_reference(reference_to, link_type);
+        self.edges.insert(reference_to.name.clone(), Type::new(edge));
+    }
+
+    /// Adds ownership whose column stores `(Id, edge)`. The edge type must implement Clone.
+    pub fn add_ownership_with_edge(&mut self, owned: &Arena, link_type: LinkType, edge: &str) {
+        self.add_ownership(owned, link_type);
+        self.edges.insert(owned.name.clone(), Type::new(edge));
+    }
+
+    /// The edge data stored alongside the link to the arena, if any.
+    pub fn get_edge_type(&self, link_to: &CamelCase) -> Option<&Type> {
+        self.edges.get(link_to)
+    }
+
     pub fn get_allocator_field(&self) -> Field {
         Field {
             visibility: Visibility::Pub,
             name: self.name.clone().into(),
-            field_type: self.allocator.get_type(self),
+            field_type: Type::new(&format!("Shared<{}>", self.allocator.get_type(self))),
         }
     }
 
     pub fn get_state_field(&self) -> Field {
         let field_name: SnakeCase = self.name.clone().into();
-        let field_type: Type = Type::new(self.name.as_str());
+        let field_type: Type = Type::new(&format!("Shared<{}>", self.name));
 
         Field {
             visibility: Default::default(),
@@ -109,16 +177,9 @@ impl Arena {
     }
 
     pub fn get_struct(&self, world: &World) -> Struct {
-        let link_fields = self.ownership.iter()
-            .chain(self.references.iter())
-            .map(|(link_to, link_type)| (world.get_arena(link_to), link_type))
-            .filter_map(|(link_to, link_type)| self.get_link_component(link_to, link_type))
-            .collect::<Vec<_>>();
-
-        let fields = link_fields.iter()
-            .chain(&self.components)
-            .chain(&self.default_components)
+        let fields = self.get_columns(world).iter()
             .map(ComponentType::get_arena_field)
+            .chain(self.get_auxiliary_fields())
             .collect();
 
         Struct {
@@ -129,6 +190,137 @@ impl Arena {
         }
     }
 
+    pub fn get_split_type(&self) -> Type {
+        Type::new(&format!("{}Mut<'a>", self.name))
+    }
+
+    /// A struct holding a mutable borrow of each arena field, so that columns can be handed out separately.
+    pub fn get_split_struct(&self, world: &World) -> Struct {
+        let aux_fields = self.get_auxiliary_fields()
+            .into_iter()
+            .map(|f| Field {
+                field_type: Type::new(&format!("&'a mut {}", f.field_type)),
+                ..f
+            });
+
+        let fields = self.get_columns(world).iter()
+            .map(|c| c.get_split_field(&self.name, true))
+            .chain(aux_fields)
+            .collect();
+
+        Struct {
+            typ: self.get_split_type(),
+            visibility: Default::default(),
+            derives: Default::default(),
+            fields,
+        }
+    }
+
+    pub fn get_columns(&self, world: &World) -> Vec<ComponentType> {
+        let link_fields = self.ownership.iter()
+            .chain(self.references.iter())
+            .map(|(link_to, link_type)| (world.get_arena(link_to), link_type))
+            .filter_map(|(link_to, link_type)| self.get_link_component(link_to, link_type));
+
+        let components = self.components.iter()
+            .chain(self.default_components.iter())
+            .flat_map(|c| std::iter::once(c.clone()).chain(c.get_next_buffer()));
+
+        link_fields
+            .chain(components)
+            .collect()
+    }
+
+    /// The names of the sparse and tag columns, which can be used in filters.
+    pub fn get_presence_column_names(&self, world: &World) -> Vec<SnakeCase> {
+        self.get_columns(world)
+            .into_iter()
+            .filter(|c| c.storage == Storage::LinearOption || c.storage == Storage::Tag)
+            .map(|c| c.name)
+            .collect()
+    }
+
+    /// Fields that are not component columns, such as indexes and the hierarchy.
+    fn get_auxiliary_fields(&self) -> Vec<Field> {
+        let id_type = self.get_id_type();
+
+        self.indexed_components()
+            .filter_map(|c| c.get_index_field(&id_type))
+            .chain(self.get_hierarchy_field())
+            .chain(self.get_events_field())
+            .collect()
+    }
+
+    /// The names of every field of the generated arena struct.
+    pub fn get_field_names(&self) -> Vec<SnakeCase> {
+        self.get_column_names()
+            .into_iter()
+            .chain(self.get_auxiliary_fields().into_iter().map(|f| f.name))
+            .collect()
+    }
+
+    /// The names of every component column of the generated arena struct.
+    pub fn get_column_names(&self) -> Vec<SnakeCase> {
+        self.components.iter()
+            .flat_map(ComponentType::get_column_names)
+            .chain(self.get_non_row_column_names())
+            .collect()
+    }
+
+    /// The columns that are not filled from the data row: links and default components.
+    pub fn get_non_row_columns(&self, world: &World) -> Vec<ComponentType> {
+        let names = self.get_non_row_column_names();
+
+        self.get_columns(world)
+            .into_iter()
+            .filter(|c| names.contains(&c.name))
+            .collect()
+    }
+
+    /// The names of the columns that are not filled from the data row: links and default components.
+    fn get_non_row_column_names(&self) -> Vec<SnakeCase> {
+        let link_columns = self.ownership.keys()
+            .chain(self.references.keys())
+            .map(|name| SnakeCase::from(name.clone()));
+
+        link_columns
+            .chain(self.default_components.iter().flat_map(ComponentType::get_column_names))
+            .collect()
+    }
+
+    fn get_hierarchy_field(&self) -> Option<Field> {
+        if !self.hierarchy {
+            return None;
+        }
+
+        Field {
+            visibility: Visibility::Pub,
+            name: "hierarchy".parse().unwrap(),
+            field_type: Type::new(&format!("Hierarchy<{}>", self.get_id_type())),
+        }.into()
+    }
+
+    fn get_events_field(&self) -> Option<Field> {
+        if !self.events {
+            return None;
+        }
+
+        Field {
+            visibility: Visibility::Pub,
+            name: "events".parse().unwrap(),
+            field_type: Type::new(&format!("EventQueue<Event<{}, {}>>", self.get_id_type(), get_link_enum_name(self))),
+        }.into()
+    }
+
+    /// The line that pushes an event for `id`, if the arena has events.
+    pub fn get_push_event(&self, event: &str, id: &str) -> Option<String> {
+        if !self.events {
+            return None;
+        }
+
+        Some(format!("self.events.push(Event::{}({}));", event, id))
+    }
+
     pub fn get_arena_type(&self) -> Type {
         self.name.clone().into()
     }
@@ -142,62 +334,595 @@ impl Arena {
             .map(ComponentType::get_data_field)
             .collect();
 
+        let derives = match self.components.iter().all(ComponentType::has_default) {
+            true => Derives::with_debug_default_clone(),
+            false => Derives::with_debug_clone(),
+        };
+
         Struct::new(name.as_str())
-            .with_derives(Derives::with_debug_clone())
+            .with_derives(derives)
             .with_fields(fields)
     }
 
     fn get_link_component(&self, link_to: &Arena, link_type: &LinkType) -> Option<ComponentType> {
         let name: SnakeCase = link_to.name.clone().into();
 
+        let data_type = match self.get_edge_type(&link_to.name) {
+            Some(edge) => Type::new(&format!("({}, {})", link_to.get_id_type(), edge)),
+            None => link_to.get_id_type(),
+        };
+
         ComponentType {
             name,
-            data_type: link_to.get_id_type(),
+            data_type,
             storage: match link_type {
                 LinkType::Required => Storage::Linear,
                 LinkType::Optional => Storage::LinearOption,
-            }
+            },
+            index: None,
+            release_on_kill: None,
+            default: false,
         }.into()
     }
 
     pub fn get_impl(&self) -> Impl {
-        let id = match self.allocator {
-            Allocator::Fixed => String::from("Id<Self>"),
-            Allocator::Generational => String::from("Valid<Self>"),
-        };
+        let mut arena_impl = Impl::from(&self.get_arena_type())
+            .add_function(self.get_insert())
+            .add_function(self.get_create())
+            .add_function(self.get_split())
+            .add_function(self.get_get_row())
+            .add_function(self.get_copy_within())
+            .add_function(self.get_copy_from())
+            .add_function(self.get_reserve())
+            .add_function(self.get_shrink_to_fit())
+            .add_function(self.get_memory());
+
+        if self.has_release() {
+            arena_impl = arena_impl.add_function(self.get_release());
+        }
+
+        if self.allocator == Allocator::Generational {
+            arena_impl = arena_impl.add_function(self.get_compact());
+        }
+
+        for component in self.indexed_components() {
+            arena_impl = arena_impl.add_function(self.get_find(component));
+        }
+
+        for split in self.splits.iter() {
+            arena_impl = arena_impl.add_function(split.get_function(self));
+        }
+
+        if self.double_buffered_components().next().is_some() {
+            arena_impl = arena_impl.add_function(self.get_swap_buffers());
+        }
+
+        for component in self.double_buffered_components() {
+            arena_impl = arena_impl.add_function(self.get_set_next(component));
+        }
+
+        let mut edges: Vec<_> = self.edges.iter().collect();
+        edges.sort_by_key(|(link_to, _)| link_to.to_string());
+
+        for (link_to, edge) in edges {
+            arena_impl = arena_impl.add_function(self.get_edge(link_to, edge));
+        }
+
+        for component in self.history_components() {
+            arena_impl = arena_impl
+                .add_function(self.get_push_history(component))
+                .add_function(self.get_latest(component))
+                .add_function(self.get_iter_history(component));
+        }
+
+        if self.hierarchy {
+            arena_impl = arena_impl
+                .add_function(self.get_set_parent())
+                .add_function(self.get_remove_parent());
+        }
+
+        arena_impl
+    }
+
+    fn get_insert(&self) -> Function {
+        let id = self.get_valid_id_type_self();
         let data_row = self.get_data_row().typ;
-        let allocator = match self.allocator {
-            Allocator::Fixed => String::from("FixedAllocator<Self>"),
-            Allocator::Generational => String::from("GenAllocator<Self>"),
-        };
 
         let mut insert = Function::new("insert")
             .with_parameters(&format!("&mut self, id: &{}, row: {}", id, data_row));
 
-        for component in self.components.iter() {
-            let line = CodeLine::new(0, &format!("self.{}.insert(id, row.{});", component.name, component.name));
+        if let Some(return_type) = self.get_insert_error() {
+            insert = insert.with_return(format!("Result<(), {}>", return_type));
+        }
+
+        for line in self.get_unique_checks(Some("id")) {
             insert = insert.add_line(line);
         }
 
-        for component in self.default_components.iter() {
-            let line = CodeLine::new(0, &format!("self.{}.insert(id, Default::default());", &component.name));
+        for line in self.get_row_writes("row") {
             insert = insert.add_line(line);
         }
 
-        let create = Function::new("create")
+        for name in self.default_components.iter().flat_map(ComponentType::get_column_names) {
+            let line = CodeLine::new(0, &format!("self.{}.insert(id, Default::default());", name));
+            insert = insert.add_line(line);
+        }
+
+        if self.hierarchy {
+            insert = insert.add_line(CodeLine::new(0, "self.hierarchy.insert(id.id());"));
+        }
+
+        if self.get_insert_error().is_some() {
+            insert = insert.add_line(CodeLine::new(0, "Ok(())"));
+        }
+
+        insert
+    }
+
+    /// Updates the indexes and writes each component of a data row, given by the expression `row`.
+    pub fn get_row_writes(&self, row: &str) -> Vec<CodeLine> {
+        let mut lines = vec![];
+
+        for component in self.indexed_components() {
+            let index = component.get_index_name();
+            match component.storage {
+                Storage::Linear | Storage::Tag | Storage::History(_) | Storage::DoubleBuffered => {
+                    lines.push(CodeLine::new(0, &format!("self.{}.insert(id.id(), {}.{}.clone());", index, row, component.name)));
+                },
+                Storage::LinearOption => {
+                    lines.push(CodeLine::new(0, &format!("match &{}.{} {{", row, component.name)));
+                    lines.push(CodeLine::new(1, &format!("Some(value) => self.{}.insert(id.id(), value.clone()),", index)));
+                    lines.push(CodeLine::new(1, &format!("None => self.{}.remove(&id.id()),", index)));
+                    lines.push(CodeLine::new(0, "}"));
+                },
+            }
+        }
+
+        for component in self.components.iter() {
+            if let Some(next) = component.get_next_buffer() {
+                lines.push(CodeLine::new(0, &format!("self.{}.insert(id, {}.{}.clone());", next.name, row, component.name)));
+            }
+
+            lines.push(CodeLine::new(0, &format!("self.{}.insert(id, {}.{});", component.name, row, component.name)));
+        }
+
+        lines
+    }
+
+    fn get_create(&self) -> Function {
+        let data_row = self.get_data_row().typ;
+        let allocator = match self.allocator {
+            Allocator::Fixed => String::from("FixedAllocator<Self>"),
+            Allocator::Generational => String::from("GenAllocator<Self>"),
+        };
+        let (insert, id) = match self.allocator {
+            Allocator::Fixed => ("self.insert(&id, row)", "id"),
+            Allocator::Generational => ("self.insert(id, row)", "id.id()"),
+        };
+
+        let mut create = Function::new("create")
             .with_parameters(&format!(
                 "&mut self, row: {row}, allocator: &mut {al}",
                 row=data_row,
                 al=allocator,
-            ))
-            .with_return(self.get_id_type().to_string())
-            .add_line(CodeLine::new(0, "let id = allocator.create();"))
-            .add_line(CodeLine::new(0, "self.insert(&id, row);"))
-            .add_line(CodeLine::new(0, "id"));
+            ));
+
+        match self.get_insert_error() {
+            Some(error) => {
+                create = create.with_return(format!("Result<{}, {}>", self.get_id_type(), error));
+
+                for line in self.get_unique_checks(None) {
+                    create = create.add_line(line);
+                }
+
+                create = create
+                    .add_line(CodeLine::new(0, "let id = allocator.create();"))
+                    .add_line(CodeLine::new(0, &format!("{}?;", insert)));
+
+                if let Some(line) = self.get_push_event("Created", id) {
+                    create = create.add_line(CodeLine::new(0, &line));
+                }
+
+                create.add_line(CodeLine::new(0, &format!("Ok({})", id)))
+            },
+            None => {
+                create = create
+                    .with_return(self.get_id_type().to_string())
+                    .add_line(CodeLine::new(0, "let id = allocator.create();"))
+                    .add_line(CodeLine::new(0, &format!("{};", insert)));
+
+                if let Some(line) = self.get_push_event("Created", id) {
+                    create = create.add_line(CodeLine::new(0, &line));
+                }
+
+                create.add_line(CodeLine::new(0, id))
+            },
+        }
+    }
+
+    fn get_split(&self) -> Function {
+        let mut split = Function::new("split")
+            .with_parameters("&mut self")
+            .with_return(format!("{}Mut<'_>", self.name))
+            .add_line(CodeLine::new(0, &format!("{}Mut {{", self.name)));
+
+        for name in self.get_field_names() {
+            split = split.add_line(CodeLine::new(1, &format!("{name}: &mut self.{name},", name=name)));
+        }
+
+        split.add_line(CodeLine::new(0, "}"))
+    }
+
+    fn get_get_row(&self) -> Function {
+        let data_row = self.get_data_row().typ;
+
+        let mut get_row = Function::new("get_row")
+            .with_parameters(&format!("&self, id: &{}", self.get_valid_id_type_self()))
+            .with_return(data_row.to_string())
+            .add_line(CodeLine::new(0, &format!("{} {{", data_row)));
+
+        for component in self.components.iter() {
+            get_row = get_row.add_line(CodeLine::new(1, &format!("{name}: self.{name}[id].clone(),", name=component.name)));
+        }
+
+        get_row.add_line(CodeLine::new(0, "}"))
+    }
+
+    /// Copies the links and default components of one entity onto another in the same arena,
+    /// along with every kept value of its history components.
+    fn get_copy_within(&self) -> Function {
+        let id = self.get_valid_id_type_self();
+        let histories = self.get_history_column_names();
+
+        let mut copy = Function::new("copy_within")
+            .with_parameters(&format!("&mut self, from: &{id}, to: &{id}", id=id));
+
+        for name in self.get_non_row_column_names().into_iter().filter(|name| !histories.contains(name)) {
+            copy = copy
+                .add_line(CodeLine::new(0, &format!("let value = self.{}[from].clone();", name)))
+                .add_line(CodeLine::new(0, &format!("self.{}.insert(to, value);", name)));
+        }
+
+        for name in histories {
+            copy = copy
+                .add_line(CodeLine::new(0, &format!("let history = self.{}.iter_history(from).cloned().collect::<Vec<_>>();", name)))
+                .add_line(CodeLine::new(0, &format!("self.{}.set_history(to, history);", name)));
+        }
+
+        copy
+    }
+
+    /// Copies the links and default components of an entity in another arena instance onto an entity in this one,
+    /// along with every kept value of its history components.
+    fn get_copy_from(&self) -> Function {
+        let id = self.get_valid_id_type_self();
+        let histories = self.get_history_column_names();
+
+        let mut copy = Function::new("copy_from")
+            .with_parameters(&format!("&mut self, source: &Self, from: &{id}, to: &{id}", id=id));
+
+        for name in self.get_non_row_column_names().into_iter().filter(|name| !histories.contains(name)) {
+            copy = copy.add_line(CodeLine::new(0, &format!("self.{name}.insert(to, source.{name}[from].clone());", name=name)));
+        }
+
+        for name in histories {
+            copy = copy.add_line(CodeLine::new(0, &format!("self.{name}.set_history(to, source.{name}.iter_history(from).cloned());", name=name)));
+        }
+
+        copy
+    }
+
+    fn get_reserve(&self) -> Function {
+        let mut reserve = Function::new("reserve")
+            .with_parameters("&mut self, additional: usize");
+
+        for name in self.get_column_names() {
+            reserve = reserve.add_line(CodeLine::new(0, &format!("self.{}.reserve(additional);", name)));
+        }
+
+        reserve
+    }
+
+    fn get_shrink_to_fit(&self) -> Function {
+        let mut shrink = Function::new("shrink_to_fit")
+            .with_parameters("&mut self");
+
+        for name in self.get_column_names() {
+            shrink = shrink.add_line(CodeLine::new(0, &format!("self.{}.shrink_to_fit();", name)));
+        }
+
+        shrink
+    }
+
+    fn get_memory(&self) -> Function {
+        let memory = self.get_column_names()
+            .iter()
+            .map(|name| format!("self.{}.memory()", name))
+            .collect::<Vec<_>>();
+
+        let memory = if memory.is_empty() {
+            String::from("0")
+        } else {
+            memory.join(" + ")
+        };
+
+        Function::new("memory")
+            .with_parameters("&self")
+            .with_return(String::from("usize"))
+            .add_line(CodeLine::new(0, &memory))
+    }
+
+    /// Moves every column, index and the hierarchy to follow a compacted allocator.
+    fn get_compact(&self) -> Function {
+        let mut compact = Function::new("compact")
+            .with_parameters("&mut self, remap: &Remap<Self>");
+
+        for name in self.get_column_names() {
+            compact = compact.add_line(CodeLine::new(0, &format!("self.{}.compact(remap);", name)));
+        }
+
+        for component in self.indexed_components() {
+            compact = compact.add_line(CodeLine::new(0, &format!("self.{}.compact(remap);", component.get_index_name())));
+        }
+
+        if self.hierarchy {
+            compact = compact.add_line(CodeLine::new(0, "self.hierarchy.compact(remap);"));
+        }
+
+        if self.events {
+            compact = compact.add_line(CodeLine::new(0, "self.events.compact(remap);"));
+        }
+
+        compact
+    }
+
+    /// The lines that detach an entity from the indexes and the hierarchy and release its component data.
+    fn get_release_lines(&self) -> Vec<String> {
+        let mut lines = vec![];
+
+        for component in self.indexed_components() {
+            lines.push(format!("self.{}.remove(&id.id());", component.get_index_name()));
+        }
+
+        if self.hierarchy {
+            lines.push(String::from("self.hierarchy.remove(id.id());"));
+        }
+
+        let released = self.components.iter()
+            .filter_map(|c| c.get_released_value().map(|value| (c, value)))
+            .chain(self.default_components.iter().map(|c| (c, "Default::default()")))
+            .flat_map(|(c, value)| c.get_column_names().into_iter().map(move |name| (name, value)))
+            .map(|(name, value)| format!("self.{}.insert(id, {});", name, value));
+
+        lines.extend(released);
+
+        // optional links are cleared so that the columns do not hold ids of entities the dead one linked to
+        let mut optional_links: Vec<SnakeCase> = self.ownership.iter()
+            .chain(self.references.iter())
+            .filter(|(_, link_type)| **link_type == LinkType::Optional)
+            .map(|(link_to, _)| link_to.clone().into())
+            .collect();
+        optional_links.sort_by_key(ToString::to_string);
+
+        lines.extend(optional_links.into_iter().map(|name| format!("self.{}.insert(id, None);", name)));
+        lines
+    }
+
+    /// True if the arena has a generated `release`, called when an entity is killed or its creation is undone.
+    pub fn has_release(&self) -> bool {
+        !self.get_release_lines().is_empty()
+    }
+
+    fn get_release(&self) -> Function {
+        let mut release = Function::new("release")
+            .with_parameters(&format!("&mut self, id: &{}", self.get_valid_id_type_self()));
+
+        for line in self.get_release_lines() {
+            release = release.add_line(CodeLine::new(0, &line));
+        }
+
+        release
+    }
+
+    /// Kills an entity that no link or relation elsewhere in the World points at. Arenas that need that
+    /// cleanup are killed through the State instead, so the World only adds this when there is none.
+    pub fn get_kill(&self) -> Function {
+        let mut kill = Function::new("kill")
+            .with_parameters("&mut self, id: GenId<Self>, allocator: &mut GenAllocator<Self>");
+
+        let push_event = self.get_push_event("Killed", "id");
+
+        if self.has_release() {
+            kill = kill
+                .add_line(CodeLine::new(0, "if let Some(valid) = allocator.verify(id) {"))
+                .add_line(CodeLine::new(1, "self.release(valid);"));
+
+            if let Some(line) = push_event {
+                kill = kill.add_line(CodeLine::new(1, &line));
+            }
+
+            kill = kill.add_line(CodeLine::new(0, "}"));
+        } else if let Some(line) = push_event {
+            kill = kill
+                .add_line(CodeLine::new(0, "if allocator.is_alive(id) {"))
+                .add_line(CodeLine::new(1, &line))
+                .add_line(CodeLine::new(0, "}"));
+        }
+
+        kill.add_line(CodeLine::new(0, "allocator.kill(id);"))
+    }
+
+    fn get_link_type(&self, link_to: &CamelCase) -> LinkType {
+        *self.ownership.get(link_to)
+            .or_else(|| self.references.get(link_to))
+            .unwrap_or_else(|| panic!("Edge data declared without a link: {} -> {}", self.name, link_to))
+    }
+
+    fn get_edge(&self, link_to: &CamelCase, edge: &Type) -> Function {
+        let column: SnakeCase = link_to.clone().into();
+
+        let (return_type, line) = match self.get_link_type(link_to) {
+            LinkType::Required => (format!("&{}", edge), format!("&self.{}[id].1", column)),
+            LinkType::Optional => (format!("Option<&{}>", edge), format!("self.{}[id].as_ref().map(|(_, edge)| edge)", column)),
+        };
+
+        Function::new(&format!("{}_edge", column))
+            .with_parameters(&format!("&self, id: &{}", self.get_valid_id_type_self()))
+            .with_return(return_type)
+            .add_line(CodeLine::new(0, &line))
+    }
+
+    /// Replaces the edge data of a one-way link, leaving the linked id as it is. Does nothing if an optional
+    /// link is empty. The edge of a two-way link is set on the State instead, which writes both sides.
+    pub fn get_set_edge(&self, link_to: &CamelCase, edge: &Type) -> Function {
+        let column: SnakeCase = link_to.clone().into();
+        let set = Function::new(&format!("set_{}_edge", column))
+            .with_parameters(&format!("&mut self, id: &{}, edge: {}", self.get_valid_id_type_self(), edge));
+
+        match self.get_link_type(link_to) {
+            LinkType::Required => set
+                .add_line(CodeLine::new(0, &format!("self.{}[id].1 = edge;", column))),
+            LinkType::Optional => set
+                .add_line(CodeLine::new(0, &format!("if let Some((_, current)) = &mut self.{}[id] {{", column)))
+                .add_line(CodeLine::new(1, "*current = edge;"))
+                .add_line(CodeLine::new(0, "}")),
+        }
+    }
+
+    fn get_find(&self, component: &ComponentType) -> Function {
+        let index = component.index.expect("get_find called on a component without an index");
+
+        Function::new(&format!("find_by_{}", component.name))
+            .with_parameters(&format!("&self, value: &{}", component.data_type))
+            .with_return(index.get_find_return_type(&self.get_id_type()))
+            .add_line(CodeLine::new(0, &format!("self.{}.get(value)", component.get_index_name())))
+    }
+
+    /// Makes the next values of every double-buffered component current, then copies them back into
+    /// the next buffer, so that values a system does not write carry over to the following tick.
+    fn get_swap_buffers(&self) -> Function {
+        let mut swap = Function::new("swap_buffers")
+            .with_parameters("&mut self");
+
+        for component in self.double_buffered_components() {
+            let next = component.get_next_buffer().unwrap().name;
+
+            swap = swap
+                .add_line(CodeLine::new(0, &format!("std::mem::swap(&mut self.{}, &mut self.{});", component.name, next)))
+                .add_line(CodeLine::new(0, &format!("self.{}.values.clone_from(&self.{}.values);", next, component.name)));
+        }
 
-        Impl::from(&self.get_arena_type())
-            .add_function(insert)
-            .add_function(create)
+        swap
+    }
+
+    fn get_set_next(&self, component: &ComponentType) -> Function {
+        Function::new(&format!("set_next_{}", component.name))
+            .with_parameters(&format!("&mut self, id: &{}, value: {}", self.get_valid_id_type_self(), component.data_type))
+            .add_line(CodeLine::new(0, &format!("self.{}[id] = value;", component.get_next_buffer().unwrap().name)))
+    }
+
+    fn get_push_history(&self, component: &ComponentType) -> Function {
+        Function::new(&format!("push_{}", component.name))
+            .with_parameters(&format!("&mut self, id: &{}, value: {}", self.get_valid_id_type_self(), component.data_type))
+            .add_line(CodeLine::new(0, &format!("self.{}.push(id, value);", component.name)))
+    }
+
+    fn get_latest(&self, component: &ComponentType) -> Function {
+        Function::new(&format!("latest_{}", component.name))
+            .with_parameters(&format!("&self, id: &{}", self.get_valid_id_type_self()))
+            .with_return(format!("&{}", component.data_type))
+            .add_line(CodeLine::new(0, &format!("self.{}.latest(id)", component.name)))
+    }
+
+    /// Iterates over the kept values of a history component, oldest first.
+    fn get_iter_history(&self, component: &ComponentType) -> Function {
+        Function::new(&format!("iter_{}_history", component.name))
+            .with_parameters(&format!("&self, id: &{}", self.get_valid_id_type_self()))
+            .with_return(format!("impl DoubleEndedIterator<Item=&{}> + '_", component.data_type))
+            .add_line(CodeLine::new(0, &format!("self.{}.iter_history(id)", component.name)))
+    }
+
+    fn get_set_parent(&self) -> Function {
+        let id = self.get_valid_id_type_self();
+
+        Function::new("set_parent")
+            .with_parameters(&format!("&mut self, child: &{id}, parent: &{id}", id=id))
+            .with_return(String::from("Result<(), HierarchyError>"))
+            .add_line(CodeLine::new(0, "self.hierarchy.set_parent(child.id(), parent.id())"))
+    }
+
+    fn get_remove_parent(&self) -> Function {
+        Function::new("remove_parent")
+            .with_parameters(&format!("&mut self, child: &{}", self.get_valid_id_type_self()))
+            .add_line(CodeLine::new(0, "self.hierarchy.remove_parent(child.id());"))
+    }
+
+    pub fn get_valid_id_type_self(&self) -> &'static str {
+        match self.allocator {
+            Allocator::Fixed => "Id<Self>",
+            Allocator::Generational => "Valid<Self>",
+        }
+    }
+
+    fn get_unique_checks(&self, id: Option<&str>) -> Vec<CodeLine> {
+        let mut lines = vec![];
+
+        for component in self.components.iter().filter(|c| c.is_unique()) {
+            let (indent, key) = match component.storage {
+                Storage::Linear | Storage::Tag | Storage::History(_) | Storage::DoubleBuffered => (0, format!("&row.{}", component.name)),
+                Storage::LinearOption => {
+                    lines.push(CodeLine::new(0, &format!("if let Some(value) = &row.{} {{", component.name)));
+                    (1, String::from("value"))
+                },
+            };
+
+            let lookup = match id {
+                Some(id) => format!("self.{}.conflict({}, &{}.id())", component.get_index_name(), key, id),
+                None => format!("self.{}.get({})", component.get_index_name(), key),
+            };
+
+            lines.push(CodeLine::new(indent, &format!("if let Some(existing) = {} {{", lookup)));
+            lines.push(CodeLine::new(indent + 1, &format!("return Err(DuplicateKey::new(\"{}\", existing));", component.name)));
+            lines.push(CodeLine::new(indent, "}"));
+
+            if component.storage == Storage::LinearOption {
+                lines.push(CodeLine::new(0, "}"));
+            }
+        }
+
+        lines
+    }
+
+    /// The error type returned by the generated `insert` and `create`, if the arena has unique components.
+    pub fn get_insert_error(&self) -> Option<String> {
+        if self.components.iter().any(ComponentType::is_unique) {
+            Some(format!("DuplicateKey<{}>", self.get_id_type()))
+        } else {
+            None
+        }
+    }
+
+    fn indexed_components(&self) -> impl Iterator<Item=&ComponentType> {
+        self.components.iter()
+            .filter(|c| c.index.is_some())
+    }
+
+    pub fn double_buffered_components(&self) -> impl Iterator<Item=&ComponentType> {
+        self.components.iter()
+            .chain(self.default_components.iter())
+            .filter(|c| c.storage == Storage::DoubleBuffered)
+    }
+
+    fn get_history_column_names(&self) -> Vec<SnakeCase> {
+        self.history_components()
+            .map(|c| c.name.clone())
+            .collect()
+    }
+
+    pub fn history_components(&self) -> impl Iterator<Item=&ComponentType> {
+        self.components.iter()
+            .chain(self.default_components.iter())
+            .filter(|c| matches!(c.storage, Storage::History(_)))
     }
 
     pub fn get_id_type(&self) -> Type {
@@ -208,6 +933,12 @@ impl Arena {
         self.allocator.get_valid_id_type(self)
     }
 
+    pub fn has_column(&self, column: &SnakeCase) -> bool {
+        self.get_field_names()
+            .iter()
+            .any(|name| name.eq(column))
+    }
+
     pub fn owns(&self, arena: &Arena) -> bool {
         self.ownership.contains_key(&arena.name)
     }
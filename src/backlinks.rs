@@ -0,0 +1,161 @@
+use crate::*;
+use code_gen::{CamelCase, SnakeCase, Field, Type};
+
+// Optional links to generational arenas are tracked in reverse, so that killing an entity can clear
+// every optional link that points at it. The bookkeeping is a RelationTable on the State, from the
+// linking entity to the linked one, kept up to date by the generated Link and Unlink implementations,
+// duplication and the journal. Link columns written directly are not tracked.
+
+/// The reverse bookkeeping for one optional link column: `from.to` holds an `Option` of an id in `to`.
+#[derive(Debug, Clone)]
+pub struct Backlink {
+    pub from: CamelCase,
+    pub to: CamelCase,
+}
+
+/// Every optional link column that points at a generational arena.
+pub fn get_backlinks(world: &World) -> Vec<Backlink> {
+    let mut backlinks = vec![];
+
+    for arena in world.arenas.iter() {
+        let mut links: Vec<_> = arena.ownership.iter()
+            .chain(arena.references.iter())
+            .filter(|(link_to, link_type)| {
+                **link_type == LinkType::Optional
+                    && world.get_arena(link_to).allocator == Allocator::Generational
+            })
+            .map(|(link_to, _)| link_to)
+            .collect();
+        links.sort_by_key(|link_to| link_to.to_string());
+
+        backlinks.extend(links.into_iter().map(|link_to| Backlink {
+            from: arena.name.clone(),
+            to: link_to.clone(),
+        }));
+    }
+
+    backlinks
+}
+
+/// The bookkeeping for the link column `from.to`, if it is an optional link to a generational arena.
+pub fn find_backlink(world: &World, from: &Arena, to: &Arena) -> Option<Backlink> {
+    get_backlinks(world)
+        .into_iter()
+        .find(|backlink| backlink.from == from.name && backlink.to == to.name)
+}
+
+impl Backlink {
+    pub fn get_name(&self) -> SnakeCase {
+        let from: SnakeCase = self.from.clone().into();
+        let to: SnakeCase = self.to.clone().into();
+
+        format!("{}_{}_backlinks", from, to).parse().unwrap()
+    }
+
+    pub fn get_state_field(&self, world: &World) -> Field {
+        Field {
+            visibility: Default::default(),
+            name: self.get_name(),
+            field_type: Type::new(&format!(
+                "Shared<RelationTable<{}, {}>>",
+                world.get_arena(&self.from).get_id_type(),
+                world.get_arena(&self.to).get_id_type(),
+            )),
+        }
+    }
+
+    /// The link column, as a path from the State.
+    pub fn get_column(&self) -> String {
+        let from: SnakeCase = self.from.clone().into();
+        let to: SnakeCase = self.to.clone().into();
+
+        format!("{}.{}", from, to)
+    }
+
+    /// Converts a value of the link column into the `Option` of the linked id, dropping any edge data.
+    pub fn get_linked_id(&self, world: &World, value: &str) -> String {
+        match world.get_arena(&self.from).get_edge_type(&self.to) {
+            Some(_) => format!("{}.as_ref().map(|(id, _)| *id)", value),
+            None => value.to_string(),
+        }
+    }
+
+    /// The lines that start tracking the link column of `id`, for an entity whose column was written directly.
+    pub fn get_track_lines(&self, world: &World, state: &str, id: &str, valid: &str) -> Vec<(usize, String)> {
+        let current = format!("{}.{}[{}]", state, self.get_column(), valid);
+
+        vec![
+            (0, format!("if let Some(linked) = {} {{", self.get_linked_id(world, &current))),
+            (1, format!("{}.{}.insert({}, linked);", state, self.get_name(), id)),
+            (0, String::from("}")),
+        ]
+    }
+
+    /// The lines that stop tracking the link column of `id`, before the column is overwritten.
+    pub fn get_untrack_lines(&self, world: &World, state: &str, id: &str, valid: &str) -> Vec<(usize, String)> {
+        let current = format!("{}.{}[{}]", state, self.get_column(), valid);
+
+        vec![
+            (0, format!("if let Some(linked) = {} {{", self.get_linked_id(world, &current))),
+            (1, format!("{}.{}.remove({}, linked);", state, self.get_name(), id)),
+            (0, String::from("}")),
+        ]
+    }
+
+    /// The lines, run by the State before `id` is killed, that clear the links pointing at it
+    /// or stop tracking its own link.
+    pub fn get_kill_lines(&self, world: &World, arena: &Arena) -> Vec<(usize, String)> {
+        let name = self.get_name();
+        let from = world.get_arena(&self.from);
+        let mut lines = vec![];
+
+        if self.from == arena.name {
+            lines.push((0, format!("self.{}.remove_source(id);", name)));
+        }
+
+        if self.to == arena.name {
+            let linked_id = self.get_linked_id(world, &format!("self.{}[valid]", self.get_column()));
+
+            lines.push((0, format!("for linking in self.{}.remove_target(id) {{", name)));
+
+            let indent = match from.allocator {
+                Allocator::Fixed => {
+                    lines.push((1, String::from("let valid = &linking;")));
+                    1
+                },
+                Allocator::Generational => {
+                    lines.push((1, format!("if let Some(valid) = allocators.{}.verify(linking) {{", from.get_state_field().name)));
+                    2
+                },
+            };
+
+            lines.push((indent, format!("if {} == Some(id) {{", linked_id)));
+            lines.push((indent + 1, format!("self.{}.insert(valid, None);", self.get_column())));
+            lines.push((indent, String::from("}")));
+
+            if from.allocator == Allocator::Generational {
+                lines.push((1, String::from("}")));
+            }
+
+            lines.push((0, String::from("}")));
+        }
+
+        lines
+    }
+
+    /// The lines that update the bookkeeping after the arena is compacted.
+    pub fn get_remap_lines(&self, arena: &Arena) -> Vec<String> {
+        let name = self.get_name();
+        let mut lines = vec![];
+
+        if self.from == arena.name {
+            lines.push(format!("self.state.{}.remap_sources(&remap);", name));
+        }
+
+        if self.to == arena.name {
+            lines.push(format!("self.state.{}.remap_targets(&remap);", name));
+        }
+
+        lines
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::{Debug, Display, Formatter, Result};
+
+/// Why a command could not be replayed by `World::apply`. Arenas are named by their type.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CommandError {
+    /// The reserved id was created in the meantime, such as by a command buffer taken from the same allocators.
+    StaleReservation { arena: &'static str },
+    /// The created row holds a unique component value that is already in use.
+    DuplicateKey { arena: &'static str, component: &'static str },
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            CommandError::StaleReservation { arena } => write!(f, "{}: reserved id is no longer available", arena),
+            CommandError::DuplicateKey { arena, component } => write!(f, "{}: duplicate value for unique component '{}'", arena, component),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Deferred operations on a world `W`, replayed in the order they were pushed. The operations are `Send`,
+/// so that a queue can be filled on another thread than the one that applies it.
+pub struct CommandQueue<W> {
+    commands: Vec<Box<dyn FnOnce(&mut W) -> std::result::Result<(), CommandError> + Send>>,
+}
+
+impl<W> Default for CommandQueue<W> {
+    fn default() -> Self {
+        Self {
+            commands: vec![],
+        }
+    }
+}
+
+impl<W> Debug for CommandQueue<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "CommandQueue({})", self.commands.len())
+    }
+}
+
+impl<W> CommandQueue<W> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn push<F: FnOnce(&mut W) -> std::result::Result<(), CommandError> + Send + 'static>(&mut self, command: F) {
+        self.commands.push(Box::new(command));
+    }
+
+    /// Replays the commands in order, stopping at the first that fails. The commands before it stay applied.
+    pub fn apply(self, world: &mut W) -> std::result::Result<(), CommandError> {
+        for command in self.commands {
+            command(world)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_replays_in_order() {
+        let mut queue = CommandQueue::<Vec<u32>>::new();
+
+        queue.push(|v: &mut Vec<u32>| { v.push(1); Ok(()) });
+        queue.push(|v: &mut Vec<u32>| { v.push(2); Ok(()) });
+        queue.push(|v: &mut Vec<u32>| { v.remove(0); Ok(()) });
+
+        let mut world = vec![0];
+        queue.apply(&mut world).unwrap();
+
+        assert_eq!(vec![1, 2], world);
+    }
+
+    #[test]
+    fn apply_stops_at_the_first_error() {
+        let mut queue = CommandQueue::<Vec<u32>>::new();
+
+        queue.push(|v: &mut Vec<u32>| { v.push(1); Ok(()) });
+        queue.push(|_: &mut Vec<u32>| Err(CommandError::StaleReservation { arena: "Ship" }));
+        queue.push(|v: &mut Vec<u32>| { v.push(2); Ok(()) });
+
+        let mut world = vec![];
+
+        assert_eq!(Err(CommandError::StaleReservation { arena: "Ship" }), queue.apply(&mut world));
+        assert_eq!(vec![1], world);
+    }
+
+    #[test]
+    fn queue_is_send() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<CommandQueue<std::rc::Rc<u32>>>();
+    }
+}
@@ -0,0 +1,210 @@
+use crate::*;
+use code_gen::*;
+
+// Commands hands out ids immediately from reservations taken from the allocators,
+// and queues the matching operations so that World::apply can replay them in order.
+// Each buffer reserves from the allocators as they were when it was taken, so two buffers taken
+// before an apply hand out the same ids; the second apply then fails with a StaleReservation.
+
+pub fn get_commands(world: &World) -> Struct {
+    let reservations = world.arenas.iter()
+        .map(|arena| Field {
+            visibility: Default::default(),
+            name: arena.get_state_field().name,
+            field_type: arena.allocator.get_reservations_type(arena),
+        });
+
+    let queue = Field {
+        visibility: Default::default(),
+        name: "queue".parse().unwrap(),
+        field_type: Type::new("CommandQueue<World>"),
+    };
+
+    Struct {
+        typ: Type::new("Commands"),
+        visibility: Default::default(),
+        derives: Default::default(),
+        fields: reservations.chain(std::iter::once(queue)).collect(),
+    }
+}
+
+pub fn impl_commands(world: &World) -> Impl {
+    let mut commands = Impl::from(&get_commands(world).typ)
+        .add_function(get_new(world));
+
+    for arena in world.arenas.iter() {
+        commands = commands.add_function(get_create(arena));
+
+        if arena.allocator == Allocator::Generational {
+            commands = commands.add_function(get_kill(world, arena));
+        }
+    }
+
+    let mut links: Vec<_> = world.links.iter()
+        .filter(|(link, _)| link.is_implemented(world))
+        .collect();
+    links.sort_by_key(|(link, _)| (link.from.to_string(), link.to.to_string()));
+
+    for (link, link_type) in links {
+        commands = commands.add_function(get_link(world, link, "Link"));
+
+        if link.is_unlinkable(world, link_type) {
+            commands = commands.add_function(get_link(world, link, "Unlink"));
+        }
+    }
+
+    commands
+}
+
+fn get_new(world: &World) -> Function {
+    let mut new = Function::new("new")
+        .with_parameters("allocators: &Allocators")
+        .with_return(String::from("Self"))
+        .add_line(CodeLine::new(0, "Commands {"));
+
+    for arena in world.arenas.iter() {
+        new = new.add_line(CodeLine::new(1, &format!("{name}: allocators.{name}.reservations(),", name=arena.get_state_field().name)));
+    }
+
+    new.add_line(CodeLine::new(1, "queue: CommandQueue::new(),"))
+        .add_line(CodeLine::new(0, "}"))
+}
+
+fn get_create(arena: &Arena) -> Function {
+    let field = arena.get_state_field().name;
+
+    let (valid, id) = match arena.allocator {
+        Allocator::Fixed => ("&valid", "valid"),
+        Allocator::Generational => ("valid", "valid.id()"),
+    };
+
+    let mut create = Function::new(&format!("create_{}", field))
+        .with_parameters(&format!("&mut self, row: {}", arena.get_data_row().typ))
+        .with_return(arena.get_id_type().to_string())
+        .add_line(CodeLine::new(0, &format!("let id = self.{}.reserve();", field)))
+        .add_line(CodeLine::new(0, "self.queue.push(move |world: &mut World| {"))
+        .add_line(CodeLine::new(1, &format!(
+            "let valid = world.allocators.{}.try_create_reserved(id).ok_or(CommandError::StaleReservation {{ arena: \"{}\" }})?;",
+            field,
+            arena.name,
+        )));
+
+    create = match arena.get_insert_error() {
+        // the slot is given back if the row cannot be inserted, so the columns keep one value per slot
+        Some(_) => create
+            .add_line(CodeLine::new(1, &format!("if let Err(error) = world.state.{}.insert({}, row) {{", field, valid)))
+            .add_line(CodeLine::new(2, &format!("let id = {};", id)))
+            .add_line(CodeLine::new(2, &format!("world.allocators.{}.uncreate(id);", field)))
+            .add_line(CodeLine::new(2, &format!("return Err(CommandError::DuplicateKey {{ arena: \"{}\", component: error.component }});", arena.name)))
+            .add_line(CodeLine::new(1, "}")),
+        None => create
+            .add_line(CodeLine::new(1, &format!("world.state.{}.insert({}, row);", field, valid))),
+    };
+
+    if arena.events {
+        create = create.add_line(CodeLine::new(1, &format!("world.state.{}.events.push(Event::Created(id));", field)));
+    }
+
+    create
+        .add_line(CodeLine::new(1, "Ok(())"))
+        .add_line(CodeLine::new(0, "});"))
+        .add_line(CodeLine::new(0, "id"))
+}
+
+fn get_kill(world: &World, arena: &Arena) -> Function {
+    let field = arena.get_state_field().name;
+
+    Function::new(&format!("kill_{}", field))
+        .with_parameters(&format!("&mut self, id: {}", arena.get_id_type()))
+        .add_line(CodeLine::new(0, "self.queue.push(move |world: &mut World| {"))
+        .add_line(CodeLine::new(1, &format!("{};", world.get_kill_call(arena, "world"))))
+        .add_line(CodeLine::new(1, "Ok(())"))
+        .add_line(CodeLine::new(0, "});"))
+}
+
+/// Queues a call to the generated `Link` or `Unlink` implementation, skipping it if a generational id has died.
+fn get_link(world: &World, link: &links::Link, link_trait: &str) -> Function {
+    let from = world.get_arena(&link.from);
+    let to = world.get_arena(&link.to);
+
+    let mut patterns = vec![];
+    let mut verifications = vec![];
+    let mut arguments = vec![];
+
+    for (arena, id) in [(from, "a"), (to, "b")].iter() {
+        match arena.allocator {
+            Allocator::Fixed => arguments.push(format!("&{}", id)),
+            Allocator::Generational => {
+                patterns.push(format!("Some({})", id));
+                verifications.push(format!("world.allocators.{}.verify({})", arena.get_state_field().name, id));
+                arguments.push(id.to_string());
+            },
+        }
+    }
+
+    let mut parameters = format!("&mut self, a: {}, b: {}", from.get_id_type(), to.get_id_type());
+
+    if link_trait == "Link" {
+        arguments.push(link.get_edge_argument(world, "edge"));
+
+        if let Some(edge) = link.get_edge_type(world) {
+            parameters += &format!(", edge: {}", edge);
+        }
+    }
+
+    let call = format!(
+        "{}::<{}, {}>::{}(&mut world.state, {});",
+        link_trait,
+        from.name,
+        to.name,
+        link_trait.to_lowercase(),
+        arguments.join(", "),
+    );
+
+    let from_field = from.get_state_field().name;
+    let to_field = to.get_state_field().name;
+
+    let mut f = Function::new(&format!("{}_{}_{}", link_trait.to_lowercase(), from_field, to_field))
+        .with_parameters(&parameters)
+        .add_line(CodeLine::new(0, "self.queue.push(move |world: &mut World| {"));
+
+    f = match patterns.len() {
+        0 => f.add_line(CodeLine::new(1, &call)),
+        1 => f
+            .add_line(CodeLine::new(1, &format!("if let {} = {} {{", patterns[0], verifications[0])))
+            .add_line(CodeLine::new(2, &call))
+            .add_line(CodeLine::new(1, "}")),
+        _ => f
+            .add_line(CodeLine::new(1, &format!("if let ({}) = ({}) {{", patterns.join(", "), verifications.join(", "))))
+            .add_line(CodeLine::new(2, &call))
+            .add_line(CodeLine::new(1, "}")),
+    };
+
+    f.add_line(CodeLine::new(1, "Ok(())"))
+        .add_line(CodeLine::new(0, "});"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_create_link_and_kill() {
+        let system = Arena::fixed("System");
+        let mut ship = Arena::generational("Ship");
+        ship.add_reference(&system, LinkType::Optional);
+
+        let world = World::new()
+            .add_arena(system)
+            .add_arena(ship);
+
+        let commands = impl_commands(&world).to_string();
+
+        for function in ["fn create_system", "fn create_ship", "fn link_ship_system", "fn unlink_ship_system", "fn kill_ship"].iter() {
+            assert!(commands.contains(function), "missing {}", function);
+        }
+
+        assert!(commands.contains("try_create_reserved(id).ok_or(CommandError::StaleReservation { arena: \"Ship\" })?;"));
+        assert!(!commands.contains("fn kill_system"));
+    }
+}
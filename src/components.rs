@@ -7,6 +7,11 @@ pub struct ComponentType {
     pub name: SnakeCase,
     pub data_type: Type,
     pub storage: Storage,
+    pub index: Option<IndexType>,
+    /// Whether the value is reset when its entity is killed. `None` releases it if the data type is known to implement `Default`.
+    pub release_on_kill: Option<bool>,
+    /// Whether the data type implements `Default`, for types the generator cannot recognize on its own.
+    pub default: bool,
 }
 
 impl ComponentType {
@@ -15,6 +20,9 @@ impl ComponentType {
             name: name.parse().unwrap(),
             data_type: data_type.parse().unwrap(),
             storage: Storage::Linear,
+            index: None,
+            release_on_kill: None,
+            default: false,
         }
     }
 
@@ -24,6 +32,9 @@ impl ComponentType {
             name: data_type.clone().into(),
             data_type: data_type.into(),
             storage: Storage::Linear,
+            index: None,
+            release_on_kill: None,
+            default: false,
         }
     }
 
@@ -32,6 +43,9 @@ impl ComponentType {
             name: name.parse().unwrap(),
             data_type: Type::from_str(data_type).unwrap(),
             storage: Storage::LinearOption,
+            index: None,
+            release_on_kill: None,
+            default: false,
         }
     }
 
@@ -41,9 +55,145 @@ impl ComponentType {
             name: data_type.clone().into(),
             data_type: data_type.into(),
             storage: Storage::LinearOption,
+            index: None,
+            release_on_kill: None,
+            default: false,
         }
     }
 
+    /// Keeps the last `len` values of each entity, starting from the row value when the entity is inserted.
+    pub fn history(name: &str, data_type: &str, len: usize) -> Self {
+        assert!(len > 0, "ComponentType::history: len must be at least 1");
+
+        Self {
+            name: name.parse().unwrap(),
+            data_type: data_type.parse().unwrap(),
+            storage: Storage::History(len),
+            index: None,
+            release_on_kill: None,
+            default: false,
+        }
+    }
+
+    /// Generates a second column, `{name}_next`, that systems write while reading the current values.
+    /// `World::swap_buffers` makes the next values current and copies them into the next column, so that
+    /// values left unwritten keep their current value. The data type must implement Clone.
+    pub fn double_buffered(name: &str, data_type: &str) -> Self {
+        Self {
+            name: name.parse().unwrap(),
+            data_type: data_type.parse().unwrap(),
+            storage: Storage::DoubleBuffered,
+            index: None,
+            release_on_kill: None,
+            default: false,
+        }
+    }
+
+    /// A marker stored as a bitset, set from a `bool` in the row.
+    pub fn tag(name: &str) -> Self {
+        Self {
+            name: name.parse().unwrap(),
+            data_type: Type::new("bool"),
+            storage: Storage::Tag,
+            index: None,
+            release_on_kill: None,
+            default: false,
+        }
+    }
+
+    pub fn indexed(mut self) -> Self {
+        self.index = Some(IndexType::Multiple);
+        self
+    }
+
+    pub fn unique(mut self) -> Self {
+        self.index = Some(IndexType::Unique);
+        self
+    }
+
+    /// Resets the value to `Default::default()` when a generational entity is killed, freeing any heap data.
+    /// Primitive values and those declared `with_default` are released without it. Sparse components are
+    /// always reset to `None`, and tags are always cleared.
+    pub fn release_on_kill(mut self) -> Self {
+        self.release_on_kill = Some(true);
+        self
+    }
+
+    /// Declares that the data type implements `Default`, so that rows can derive it and the value is released on kill.
+    pub fn with_default(mut self) -> Self {
+        self.default = true;
+        self
+    }
+
+    /// Keeps the value of a killed entity until its slot is reused, for values that are cheap to keep.
+    pub fn keep_on_kill(mut self) -> Self {
+        self.release_on_kill = Some(false);
+        self
+    }
+
+    /// The value written over the component when its entity is killed, if any.
+    pub fn get_released_value(&self) -> Option<&'static str> {
+        match self.storage {
+            Storage::LinearOption => Some("None"),
+            Storage::Tag => Some("false"),
+            Storage::Linear | Storage::History(_) | Storage::DoubleBuffered => {
+                if self.release_on_kill.unwrap_or_else(|| self.has_default()) {
+                    Some("Default::default()")
+                } else {
+                    None
+                }
+            },
+        }
+    }
+
+    /// The column holding the next values of a double-buffered component.
+    pub fn get_next_buffer(&self) -> Option<ComponentType> {
+        if self.storage != Storage::DoubleBuffered {
+            return None;
+        }
+
+        ComponentType {
+            name: format!("{}_next", self.name).parse().unwrap(),
+            data_type: self.data_type.clone(),
+            storage: Storage::Linear,
+            index: None,
+            release_on_kill: self.release_on_kill,
+            default: self.default,
+        }.into()
+    }
+
+    /// The names of the arena columns holding the component, including the next buffer.
+    pub fn get_column_names(&self) -> Vec<SnakeCase> {
+        std::iter::once(self.name.clone())
+            .chain(self.get_next_buffer().map(|next| next.name))
+            .collect()
+    }
+
+    /// Whether the row value can be left out when building a row: sparse components default to `None` and tags to `false`.
+    pub fn is_optional_in_row(&self) -> bool {
+        matches!(self.storage, Storage::LinearOption | Storage::Tag)
+    }
+
+    /// Whether the row value is known to implement `Default`: declared with `with_default`, or a primitive type.
+    /// Other types, including `String` and `Vec`, must be declared, as a type alias or a shadowing type of the same
+    /// name could not be told apart from them.
+    pub fn has_default(&self) -> bool {
+        const PRIMITIVE_TYPES: [&str; 16] = [
+            "bool", "char",
+            "u8", "u16", "u32", "u64", "u128", "usize",
+            "i8", "i16", "i32", "i64", "i128", "isize",
+            "f32", "f64",
+        ];
+
+        self.default
+            || self.is_optional_in_row()
+            || PRIMITIVE_TYPES.contains(&self.data_type.to_string().as_str())
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.index == Some(IndexType::Unique)
+    }
+
     pub fn get_arena_field(&self) -> Field {
         Field {
             visibility: Visibility::Pub,
@@ -52,6 +202,18 @@ impl ComponentType {
         }
     }
 
+    /// A borrow of the column for a split struct, mutable only if the split writes to it.
+    pub fn get_split_field(&self, owner: &CamelCase, mutable: bool) -> Field {
+        let column_type = self.storage.get_column_type(owner.as_str(), &self.data_type);
+        let borrow = if mutable { "&'a mut" } else { "&'a" };
+
+        Field {
+            visibility: Visibility::Pub,
+            name: self.name.clone(),
+            field_type: Type::new(&format!("{} {}", borrow, column_type)),
+        }
+    }
+
     pub fn get_data_field(&self) -> Field {
         Field {
             visibility: Default::default(),
@@ -59,25 +221,71 @@ impl ComponentType {
             field_type: self.storage.get_row_data_type(&self.data_type)
         }
     }
+
+    pub fn get_index_name(&self) -> SnakeCase {
+        format!("{}_index", self.name).parse().unwrap()
+    }
+
+    pub fn get_index_field(&self, id_type: &Type) -> Option<Field> {
+        let index = self.index?;
+
+        Field {
+            visibility: Visibility::Pub,
+            name: self.get_index_name(),
+            field_type: index.get_index_type(id_type, &self.data_type),
+        }.into()
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum IndexType {
+    Unique,
+    Multiple,
+}
+
+impl IndexType {
+    pub fn get_index_type(&self, id_type: &Type, data_type: &Type) -> Type {
+        let s = match self {
+            IndexType::Unique => format!("UniqueIndex<{}, {}>", id_type, data_type),
+            IndexType::Multiple => format!("MultiIndex<{}, {}>", id_type, data_type),
+        };
+        Type::new(s.as_str())
+    }
+
+    pub fn get_find_return_type(&self, id_type: &Type) -> String {
+        match self {
+            IndexType::Unique => format!("Option<{}>", id_type),
+            IndexType::Multiple => format!("&[{}]", id_type),
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Storage {
     Linear,
     LinearOption,
+    Tag,
+    History(usize),
+    DoubleBuffered,
 }
 
 impl Storage {
     pub fn get_component_data_type(&self, data_type: Type) -> Type {
+        self.get_column_type("Self", &data_type)
+    }
+
+    pub fn get_column_type(&self, owner: &str, data_type: &Type) -> Type {
         match self {
-            Storage::Linear => format!("Component<Self, {}>", data_type),
-            Storage::LinearOption => format!("Component<Self, Option<{}>>", data_type),
+            Storage::Linear | Storage::DoubleBuffered => format!("Component<{}, {}>", owner, data_type),
+            Storage::LinearOption => format!("Sparse<{}, {}>", owner, data_type),
+            Storage::Tag => format!("Tag<{}>", owner),
+            Storage::History(len) => format!("History<{}, {}, {}>", owner, data_type, len),
         }.parse().unwrap()
     }
 
     pub fn get_row_data_type(&self, data_type: &Type) -> Type {
         let s = match self {
-            Storage::Linear => data_type.to_string(),
+            Storage::Linear | Storage::Tag | Storage::History(_) | Storage::DoubleBuffered => data_type.to_string(),
             Storage::LinearOption => format!("Option<{}>", data_type),
         };
         Type::new(s.as_str())
@@ -0,0 +1,488 @@
+use crate::*;
+use code_gen::*;
+
+// Duplicating an entity copies its data row, links, default components and histories, then
+// deep-copies each entity it owns and links the copy to its new owner. Every copy is recorded in
+// `Duplicates`, from the original id to the id of the copy, so that once the whole subtree has been
+// copied, references between the originals are re-linked between the copies. A reference that leaves
+// the subtree is kept if it is one-way, but cleared if the other side links back, as it can only link
+// back to one of them. The ids of another World mean nothing in the target, so the _into variant
+// clears every reference that leaves the subtree. A reference is only cleared if it is optional, so
+// an arena whose subtree would need to clear a required reference has no duplicate. Arenas with
+// unique components, or that own such arenas, cannot be duplicated.
+
+/// `Duplicates`, with a map from original to copy for each arena.
+pub fn get_duplicates_struct(world: &World) -> Struct {
+    let fields = world.arenas.iter()
+        .map(|arena| Field {
+            visibility: Visibility::Pub,
+            name: arena.get_state_field().name,
+            field_type: Type::new(&format!(
+                "std::collections::HashMap<{id}, {id}>",
+                id=arena.get_id_type(),
+            )),
+        })
+        .collect();
+
+    Struct::new("Duplicates")
+        .with_derives(Derives::with_debug_default_clone())
+        .with_fields(fields)
+}
+
+pub fn get_duplicate_functions(world: &World) -> Vec<Function> {
+    let mut duplicates = vec![];
+    let mut copied: Vec<(&CamelCase, bool)> = vec![];
+
+    for arena in world.arenas.iter() {
+        let subtree = owned_arenas(world, arena);
+
+        if subtree.iter().any(|a| a.get_insert_error().is_some()) {
+            continue;
+        }
+
+        for into in [false, true] {
+            if can_duplicate(world, arena, into) {
+                duplicates.push(get_duplicate(world, arena, into));
+                copied.extend(subtree.iter().map(|a| (&a.name, into)));
+            }
+        }
+    }
+
+    // only the arenas copied by some duplicate need a copy function
+    let mut functions = vec![];
+
+    for arena in world.arenas.iter() {
+        for into in [false, true] {
+            if copied.contains(&(&arena.name, into)) {
+                functions.push(get_copy_subtree(world, arena, into));
+            }
+        }
+    }
+
+    functions.extend(duplicates);
+    functions
+}
+
+/// The arena and every arena it owns, directly or through owned arenas.
+fn owned_arenas<'a>(world: &'a World, arena: &'a Arena) -> Vec<&'a Arena> {
+    let mut stack = vec![arena];
+    let mut visited: Vec<&Arena> = vec![];
+
+    while let Some(arena) = stack.pop() {
+        if visited.iter().any(|a| a.name == arena.name) {
+            continue;
+        }
+
+        visited.push(arena);
+        stack.extend(arena.ownership.keys().map(|owned| world.get_arena(owned)));
+    }
+
+    visited
+}
+
+/// A reference from an owned entity to its owner within the subtree, which always points at a copy.
+fn is_reference_to_owner(world: &World, root: &Arena, arena: &Arena, link_to: &CamelCase) -> bool {
+    arena.name != root.name
+        && world.get_arena(link_to).owns(arena)
+        && owned_arenas(world, root).iter().any(|a| a.name == *link_to)
+}
+
+/// Whether a reference that leaves the subtree must be cleared on the copy.
+fn needs_clear(world: &World, root: &Arena, arena: &Arena, link_to: &CamelCase, into: bool) -> bool {
+    let other = world.get_arena(link_to);
+    let two_way = other.owns(arena) || other.references(arena);
+
+    (into || two_way) && !is_reference_to_owner(world, root, arena, link_to)
+}
+
+/// Every reference that may need clearing is optional.
+fn can_duplicate(world: &World, root: &Arena, into: bool) -> bool {
+    owned_arenas(world, root).iter().all(|arena| {
+        arena.references.iter().all(|(link_to, link_type)| {
+            *link_type == LinkType::Optional || !needs_clear(world, root, arena, link_to, into)
+        })
+    })
+}
+
+/// The expression for the copy in the destination World, as an id that can index its columns.
+fn get_copy_valid(arena: &Arena, dest: &str, copy: &str) -> String {
+    match arena.allocator {
+        Allocator::Fixed => format!("&{}", copy),
+        Allocator::Generational => format!("{}.allocators.{}.verify({})?", dest, arena.get_state_field().name, copy),
+    }
+}
+
+/// Copies the entity and everything it owns, recording each copy in `copies`. The references of the
+/// copies still point at the originals; `duplicate_{arena}` re-links them once the subtree is copied.
+fn get_copy_subtree(world: &World, arena: &Arena, into: bool) -> Function {
+    let field = arena.get_state_field().name;
+    let dest = if into { "target" } else { "self" };
+
+    let (id_param, return_type, source, result) = match arena.allocator {
+        Allocator::Fixed => (
+            format!("id: &{}", arena.get_id_type()),
+            arena.get_id_type().to_string(),
+            String::from("id"),
+            "copy",
+        ),
+        Allocator::Generational => (
+            format!("id: {}", arena.get_id_type()),
+            format!("Option<{}>", arena.get_id_type()),
+            format!("self.allocators.{}.verify(id)?", field),
+            "Some(copy)",
+        ),
+    };
+    let copy = get_copy_valid(arena, dest, "copy");
+
+    let (name, parameters) = if into {
+        (format!("copy_{}_subtree_into", field), format!("&self, {}, target: &mut World, copies: &mut Duplicates", id_param))
+    } else {
+        (format!("copy_{}_subtree", field), format!("&mut self, {}, copies: &mut Duplicates", id_param))
+    };
+
+    let original = match arena.allocator {
+        Allocator::Fixed => "*id",
+        Allocator::Generational => "id",
+    };
+
+    let mut lines = vec![
+        CodeLine::new(0, &format!("let row = self.state.{}.get_row({});", field, source)),
+        CodeLine::new(0, &format!("let copy = {d}.state.{f}.create(row, &mut {d}.allocators.{f});", d=dest, f=field)),
+        CodeLine::new(0, &format!("copies.{}.insert({}, copy);", field, original)),
+    ];
+
+    if into {
+        lines.push(CodeLine::new(0, &format!("target.state.{f}.copy_from(&self.state.{f}, {}, {});", source, copy, f=field)));
+
+        // the ids of the source World are cleared before they are tracked; owned entities are linked again
+        // once they are copied, and references are re-linked once the subtree is copied
+        let mut cleared: Vec<_> = arena.references.iter()
+            .chain(arena.ownership.iter())
+            .filter(|(_, link_type)| **link_type == LinkType::Optional)
+            .map(|(link_to, _)| world.get_arena(link_to).get_state_field().name)
+            .collect();
+        cleared.sort_by_key(ToString::to_string);
+        cleared.dedup();
+
+        for column in cleared {
+            lines.push(CodeLine::new(0, &format!("target.state.{}.{}.insert({}, None);", field, column, copy)));
+        }
+    } else {
+        lines.push(CodeLine::new(0, &format!("self.state.{}.copy_within({}, {});", field, source, copy)));
+    }
+
+    for backlink in get_backlinks(world).iter().filter(|backlink| backlink.from == arena.name) {
+        for (indent, line) in backlink.get_track_lines(world, &format!("{}.state", dest), "copy", &copy) {
+            lines.push(CodeLine::new(indent, &line));
+        }
+    }
+
+    let mut ownership: Vec<_> = arena.ownership.iter().collect();
+    ownership.sort_by_key(|(name, _)| name.to_string());
+
+    for (owned, link_type) in ownership {
+        let child_arena = world.get_arena(owned);
+        let child_field = child_arena.get_state_field().name;
+
+        let edged = arena.get_edge_type(owned).is_some();
+        let (child, value) = if edged { ("(child, edge)", ".clone()") } else { ("child", "") };
+
+        let mut indent = 0;
+
+        if *link_type == LinkType::Optional {
+            lines.push(CodeLine::new(indent, &format!("let child = self.state.{}.{}[{}]{};", field, child_field, source, value)));
+            lines.push(CodeLine::new(indent, &format!("if let Some({}) = child {{", child)));
+            indent += 1;
+        } else {
+            lines.push(CodeLine::new(indent, &format!("let {} = self.state.{}.{}[{}]{};", child, field, child_field, source, value)));
+        }
+
+        let edge = if edged { "edge" } else { "()" };
+        let link = |child: &str| format!(
+            "Link::<{}, {}>::link(&mut {}.state, {}, {}, {});",
+            arena.name,
+            child_arena.name,
+            dest,
+            copy,
+            child,
+            edge,
+        );
+
+        let call = |child: &str| if into {
+            format!("self.copy_{}_subtree_into({}, target, copies)", child_field, child)
+        } else {
+            format!("self.copy_{}_subtree({}, copies)", child_field, child)
+        };
+
+        match child_arena.allocator {
+            Allocator::Fixed => {
+                lines.push(CodeLine::new(indent, &format!("let child = {};", call("&child"))));
+                lines.push(CodeLine::new(indent, &link("&child")));
+            },
+            Allocator::Generational => {
+                lines.push(CodeLine::new(indent, &format!("if let Some(child) = {} {{", call("child"))));
+                lines.push(CodeLine::new(indent + 1, &format!("if let Some(child) = {}.allocators.{}.verify(child) {{", dest, child_field)));
+                lines.push(CodeLine::new(indent + 2, &link("child")));
+                lines.push(CodeLine::new(indent + 1, "}"));
+                lines.push(CodeLine::new(indent, "}"));
+            },
+        }
+
+        if *link_type == LinkType::Optional {
+            lines.push(CodeLine::new(0, "}"));
+        }
+    }
+
+    lines.push(CodeLine::new(0, result));
+
+    let mut function = Function::new(&name)
+        .with_parameters(&parameters)
+        .with_return(return_type);
+
+    for line in lines {
+        function = function.add_line(line);
+    }
+
+    function
+}
+
+fn get_duplicate(world: &World, root: &Arena, into: bool) -> Function {
+    let field = root.get_state_field().name;
+    let dest = if into { "target" } else { "self" };
+
+    let (id_param, return_type, copy, result) = match root.allocator {
+        Allocator::Fixed => (
+            format!("id: &{}", root.get_id_type()),
+            root.get_id_type().to_string(),
+            if into { format!("self.copy_{}_subtree_into(id, target, &mut copies)", field) } else { format!("self.copy_{}_subtree(id, &mut copies)", field) },
+            "copy",
+        ),
+        Allocator::Generational => (
+            format!("id: {}", root.get_id_type()),
+            format!("Option<{}>", root.get_id_type()),
+            if into { format!("self.copy_{}_subtree_into(id, target, &mut copies)?", field) } else { format!("self.copy_{}_subtree(id, &mut copies)?", field) },
+            "Some(copy)",
+        ),
+    };
+
+    let (name, parameters) = if into {
+        (format!("duplicate_{}_into", field), format!("&self, {}, target: &mut World", id_param))
+    } else {
+        (format!("duplicate_{}", field), format!("&mut self, {}", id_param))
+    };
+
+    let mut lines = vec![
+        (0, String::from("let mut copies = Duplicates::default();")),
+        (0, format!("let copy = {};", copy)),
+    ];
+
+    let mut subtree = owned_arenas(world, root);
+    subtree.sort_by_key(|arena| arena.name.to_string());
+
+    for arena in subtree {
+        let mut references: Vec<_> = arena.references.iter().collect();
+        references.sort_by_key(|(link_to, _)| link_to.to_string());
+
+        for (link_to, link_type) in references {
+            lines.extend(get_relink_lines(world, root, arena, link_to, *link_type, dest, into));
+        }
+    }
+
+    lines.push((0, String::from(result)));
+
+    let mut duplicate = Function::new(&name)
+        .with_parameters(&parameters)
+        .with_return(return_type);
+
+    for (indent, line) in lines {
+        duplicate = duplicate.add_line(CodeLine::new(indent, &line));
+    }
+
+    duplicate
+}
+
+/// Points the reference of each copy at the copy of the entity its original referenced,
+/// or clears it if that entity was not copied and the reference cannot be kept.
+fn get_relink_lines(
+    world: &World,
+    root: &Arena,
+    arena: &Arena,
+    link_to: &CamelCase,
+    link_type: LinkType,
+    dest: &str,
+    into: bool,
+) -> Vec<(usize, String)> {
+    let field = arena.get_state_field().name;
+    let other = world.get_arena(link_to);
+    let other_field = other.get_state_field().name;
+    let column = format!("{}.{}", field, other_field);
+    let edged = arena.get_edge_type(link_to).is_some();
+
+    let mut lines = vec![(0, format!("for (original, copy) in copies.{}.iter() {{", field))];
+
+    let indent = match arena.allocator {
+        Allocator::Fixed => {
+            lines.push((1, String::from("let (original_valid, copy_valid) = (original, copy);")));
+            1
+        },
+        Allocator::Generational => {
+            lines.push((1, format!(
+                "if let (Some(original_valid), Some(copy_valid)) = (self.allocators.{f}.verify(*original), {}.allocators.{f}.verify(*copy)) {{",
+                dest,
+                f=field,
+            )));
+            2
+        },
+    };
+
+    let value = format!("self.state.{}[original_valid]", column);
+    let linked = match (link_type, edged) {
+        (LinkType::Required, false) => format!("Some({})", value),
+        (LinkType::Required, true) => format!("Some({}.clone())", value),
+        (LinkType::Optional, false) => value,
+        (LinkType::Optional, true) => format!("{}.clone()", value),
+    };
+    let lookup = match edged {
+        true => format!("linked.and_then(|(linked, edge)| copies.{}.get(&linked).map(|copy| (*copy, edge)))", other_field),
+        false => format!("linked.and_then(|linked| copies.{}.get(&linked).map(|copy| (*copy, ())))", other_field),
+    };
+
+    lines.push((indent, format!("let linked = {};", linked)));
+    lines.push((indent, format!("match {} {{", lookup)));
+    lines.push((indent + 1, String::from("Some((linked_copy, edge)) => {")));
+
+    let link_indent = match other.allocator {
+        Allocator::Fixed => {
+            lines.push((indent + 2, String::from("let linked_valid = &linked_copy;")));
+            indent + 2
+        },
+        Allocator::Generational => {
+            lines.push((indent + 2, format!("if let Some(linked_valid) = {}.allocators.{}.verify(linked_copy) {{", dest, other_field)));
+            indent + 3
+        },
+    };
+
+    let link = Link { from: arena.name.clone(), to: link_to.clone() };
+    let link = match link.is_implemented(world) {
+        true => format!("Link::<{}, {}>::link(&mut {}.state, copy_valid, linked_valid, edge);", arena.name, other.name, dest),
+        false => format!("Link::<{}, {}>::link(&mut {}.state, linked_valid, copy_valid, edge);", other.name, arena.name, dest),
+    };
+    lines.push((link_indent, link));
+
+    if other.allocator == Allocator::Generational {
+        lines.push((indent + 2, String::from("}")));
+    }
+
+    lines.push((indent + 1, String::from("},")));
+    lines.push((indent + 1, String::from("None => {")));
+
+    if link_type == LinkType::Optional && needs_clear(world, root, arena, link_to, into) {
+        if let Some(backlink) = find_backlink(world, arena, other) {
+            let untrack = backlink.get_untrack_lines(world, &format!("{}.state", dest), "*copy", "copy_valid");
+            lines.extend(untrack.into_iter().map(|(i, line)| (indent + 2 + i, line)));
+        }
+
+        lines.push((indent + 2, format!("{}.state.{}.insert(copy_valid, None);", dest, column)));
+    }
+
+    lines.push((indent + 1, String::from("},")));
+    lines.push((indent, String::from("}")));
+
+    if arena.allocator == Allocator::Generational {
+        lines.push((1, String::from("}")));
+    }
+
+    lines.push((0, String::from("}")));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::fleet;
+
+    #[test]
+    fn duplicate_copies_owned_children() {
+        let world = fleet();
+        let generated = world.impl_world().to_string();
+
+        assert!(generated.contains("if let Some(child) = self.copy_engine_subtree(child, copies) {"));
+        assert!(generated.contains("Link::<Ship, Engine>::link(&mut self.state, self.allocators.ship.verify(copy)?, child, ());"));
+        assert!(generated.contains("if let Some(child) = self.copy_engine_subtree_into(child, target, copies) {"));
+        assert!(generated.contains("Link::<Ship, Engine>::link(&mut target.state, target.allocators.ship.verify(copy)?, child, ());"));
+    }
+
+    #[test]
+    fn duplicate_relinks_references_between_copies() {
+        let world = fleet();
+        let generated = world.impl_world().to_string();
+
+        assert!(generated.contains("copies.crew.insert(id, copy);"));
+        assert!(generated.contains("for (original, copy) in copies.crew.iter() {"));
+        assert!(generated.contains("match linked.and_then(|linked| copies.engine.get(&linked).map(|copy| (*copy, ()))) {"));
+        assert!(generated.contains("Link::<Crew, Engine>::link(&mut self.state, copy_valid, linked_valid, edge);"));
+    }
+
+    #[test]
+    fn duplicate_into_clears_links_to_the_source_world() {
+        let world = fleet();
+        let generated = world.impl_world().to_string();
+
+        assert!(generated.contains("target.state.ship.system.insert(target.allocators.ship.verify(copy)?, None);"));
+        assert!(generated.contains("target.state.ship.engine.insert(target.allocators.ship.verify(copy)?, None);"));
+        assert!(generated.contains("target.state.ship.system.insert(copy_valid, None);"));
+    }
+
+    #[test]
+    fn duplicate_clears_two_way_references_that_leave_the_subtree() {
+        let mut body = Arena::fixed("Body");
+        let mut colony = Arena::generational("Colony");
+        colony.add_reference(&body, LinkType::Optional);
+        body.add_ownership(&colony, LinkType::Optional);
+
+        let world = World::new()
+            .add_arena(body)
+            .add_arena(colony);
+
+        let generated = world.impl_world().to_string();
+
+        assert!(generated.contains("fn duplicate_colony("));
+        assert!(generated.contains("self.state.colony.body.insert(copy_valid, None);"));
+        assert!(!generated.contains("self.state.body.colony.insert(copy_valid, None);"));
+    }
+
+    #[test]
+    fn duplicate_requires_optional_references_where_they_are_cleared() {
+        let system = Arena::fixed("System");
+        let mut body = Arena::fixed("Body");
+        body.add_reference(&system, LinkType::Required);
+
+        let mut planet = Arena::fixed("Planet");
+        let mut moon = Arena::generational("Moon");
+        moon.add_reference(&planet, LinkType::Required);
+        planet.add_ownership(&moon, LinkType::Optional);
+
+        let world = World::new()
+            .add_arena(system)
+            .add_arena(body)
+            .add_arena(planet)
+            .add_arena(moon);
+
+        let generated = world.impl_world().to_string();
+
+        assert!(generated.contains("fn duplicate_body("));
+        assert!(!generated.contains("fn duplicate_body_into("));
+        assert!(generated.contains("fn duplicate_planet("));
+        assert!(!generated.contains("fn duplicate_moon("));
+    }
+
+    #[test]
+    fn duplicate_keeps_histories() {
+        let world = fleet();
+        let engine = world.get_arena(&"Engine".parse().unwrap());
+        let generated = engine.get_impl().to_string();
+
+        assert!(generated.contains("self.heat.set_history(to, history);"));
+        assert!(generated.contains("self.heat.set_history(to, source.heat.iter_history(from).cloned());"));
+    }
+}
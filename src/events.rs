@@ -0,0 +1,147 @@
+use crate::allocators::Remap;
+use crate::ids::GenId;
+
+/// A change to an entity, pushed by the generated `create`, `kill` and `Link`/`Unlink` implementations
+/// of arenas with events. Link events carry the entity on the other side of the link, as a generated
+/// `{Arena}Link` with a variant for each arena the arena links with.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Event<ID, L> {
+    Created(ID),
+    Killed(ID),
+    Linked(ID, L),
+    Unlinked(ID, L),
+}
+
+impl<ID: Copy, L> Event<ID, L> {
+    pub fn id(&self) -> ID {
+        match self {
+            Event::Created(id) | Event::Killed(id) | Event::Linked(id, _) | Event::Unlinked(id, _) => *id,
+        }
+    }
+}
+
+/// Events waiting to be handled, in the order they happened. Systems usually drain the queue each tick.
+#[derive(Debug, Clone)]
+pub struct EventQueue<E> {
+    events: Vec<E>,
+}
+
+impl<E> Default for EventQueue<E> {
+    fn default() -> Self {
+        Self {
+            events: vec![],
+        }
+    }
+}
+
+impl<E> EventQueue<E> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn push(&mut self, event: E) {
+        self.events.push(event);
+    }
+
+    pub fn drain(&mut self) -> std::vec::Drain<'_, E> {
+        self.events.drain(..)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, E> {
+        self.events.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl<ID, L> EventQueue<Event<ID, L>> {
+    /// Updates the other side of each link event, for when the arena on that side is compacted.
+    pub fn remap_links(&mut self, mut remap: impl FnMut(&mut L)) {
+        for event in self.events.iter_mut() {
+            if let Event::Linked(_, link) | Event::Unlinked(_, link) = event {
+                remap(link);
+            }
+        }
+    }
+}
+
+impl<T, L> EventQueue<Event<GenId<T>, L>> {
+    /// Updates the ids of living entities to follow a compacted allocator. Ids of dead entities are kept,
+    /// so that `Killed` events still identify the entity that was killed.
+    pub fn compact(&mut self, remap: &Remap<T>) {
+        for event in self.events.iter_mut() {
+            let id = match event {
+                Event::Created(id) | Event::Killed(id) | Event::Linked(id, _) | Event::Unlinked(id, _) => id,
+            };
+
+            if let Some(new) = remap.get(*id) {
+                *id = new;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocators::GenAllocator;
+
+    #[derive(Debug)]
+    struct Ship;
+
+    #[test]
+    fn drain_empties_in_order() {
+        let mut queue = EventQueue::new();
+
+        queue.push(Event::Created(0));
+        queue.push(Event::Linked(0, 1));
+        queue.push(Event::Killed(0));
+
+        let events: Vec<_> = queue.drain().collect();
+
+        assert_eq!(vec![Event::Created(0), Event::Linked(0, 1), Event::Killed(0)], events);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn compact_keeps_killed_ids() {
+        let mut allocator = GenAllocator::<Ship>::new();
+        let mut queue = EventQueue::new();
+
+        let a = allocator.create().id;
+        let b = allocator.create().id;
+        queue.push(Event::<_, ()>::Created(b));
+        allocator.kill(a);
+        queue.push(Event::Killed(a));
+
+        let remap = allocator.compact();
+        queue.compact(&remap);
+
+        let events: Vec<_> = queue.iter().map(Event::id).collect();
+        assert_eq!(vec![remap.get(b).unwrap(), a], events);
+    }
+
+    #[test]
+    fn remap_links_updates_the_other_side() {
+        let mut queue = EventQueue::new();
+
+        queue.push(Event::Created(0));
+        queue.push(Event::Linked(0, 5));
+        queue.push(Event::Unlinked(0, 6));
+
+        queue.remap_links(|link| *link -= 5);
+
+        let events: Vec<_> = queue.drain().collect();
+        assert_eq!(vec![Event::Created(0), Event::Linked(0, 0), Event::Unlinked(0, 1)], events);
+    }
+}
@@ -0,0 +1,38 @@
+use crate::*;
+use code_gen::{SnakeCase, Function, CodeLine};
+
+/// A generated iterator over the entities of an arena that have every listed sparse component or tag.
+/// Presence is tracked by the columns themselves, so the iterator skips entities without scanning them.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pub name: SnakeCase,
+    pub columns: Vec<SnakeCase>,
+}
+
+impl Filter {
+    pub fn new(name: &str, columns: &[&str]) -> Self {
+        Filter {
+            name: name.parse().unwrap(),
+            columns: columns.iter().map(|c| c.parse().unwrap()).collect(),
+        }
+    }
+
+    pub fn get_function(&self, arena: &Arena) -> Function {
+        let field = arena.get_state_field().name;
+
+        let item = match arena.allocator {
+            Allocator::Fixed => format!("Id<{}>", arena.name),
+            Allocator::Generational => format!("&Valid<{}>", arena.name),
+        };
+
+        let columns = self.columns.iter()
+            .map(|column| format!("&self.state.{}.{}", field, column))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Function::new(&format!("iter_{}_{}", field, self.name))
+            .with_parameters("&self")
+            .with_return(format!("impl Iterator<Item={}> + '_", item))
+            .add_line(CodeLine::new(0, &format!("self.allocators.{}.filter(&[{}])", field, columns)))
+    }
+}
@@ -0,0 +1,33 @@
+use crate::*;
+
+/// A journaled World shared by the generator tests: systems with unique names, and ships that form
+/// a hierarchy, own their engines and crews, and reference the system they are in. Each crew
+/// references the engine it works on.
+pub fn fleet() -> World {
+    let system = Arena::fixed("System")
+        .add_component(ComponentType::dense("name", "String").unique());
+
+    let engine = Arena::generational("Engine")
+        .add_component(ComponentType::history("heat", "f64", 4));
+
+    let mut crew = Arena::generational("Crew")
+        .add_component(ComponentType::dense("rank", "u8"));
+    crew.add_reference(&engine, LinkType::Optional);
+
+    let mut ship = Arena::generational("Ship")
+        .add_component(ComponentType::sparse("class", "u8").indexed())
+        .add_component(ComponentType::history("speed", "f64", 4))
+        .add_default_component(ComponentType::dense("heading", "f64"))
+        .add_hierarchy()
+        .add_events();
+    ship.add_ownership(&engine, LinkType::Optional);
+    ship.add_ownership(&crew, LinkType::Optional);
+    ship.add_reference(&system, LinkType::Optional);
+
+    World::new()
+        .add_arena(system)
+        .add_arena(engine)
+        .add_arena(crew)
+        .add_arena(ship)
+        .add_journal()
+}
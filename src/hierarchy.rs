@@ -0,0 +1,301 @@
+use crate::allocators::Remap;
+use crate::ids::{ArenaId, GenId};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter, Result};
+
+/// Parent and children columns for an arena whose entities form a forest.
+#[derive(Debug, Clone)]
+pub struct Hierarchy<ID> {
+    ids: Vec<Option<ID>>,
+    parents: Vec<Option<ID>>,
+    children: Vec<Vec<ID>>,
+}
+
+impl<ID> Default for Hierarchy<ID> {
+    fn default() -> Self {
+        Self {
+            ids: vec![],
+            parents: vec![],
+            children: vec![],
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum HierarchyError {
+    SelfParent,
+    Cycle,
+}
+
+impl Display for HierarchyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            HierarchyError::SelfParent => write!(f, "an entity cannot be its own parent"),
+            HierarchyError::Cycle => write!(f, "the new parent is a descendant of the child"),
+        }
+    }
+}
+
+impl std::error::Error for HierarchyError {}
+
+impl<ID: ArenaId> Hierarchy<ID> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds the entity as a root. If the slot is being reused, the previous occupant is removed first.
+    pub fn insert(&mut self, id: ID) {
+        let index = id.slot();
+
+        match index {
+            index if index < self.ids.len() => {
+                if let Some(previous) = self.ids[index] {
+                    self.remove(previous);
+                }
+                self.ids[index] = Some(id);
+            },
+            index if index == self.ids.len() => {
+                self.ids.push(Some(id));
+                self.parents.push(None);
+                self.children.push(vec![]);
+            },
+            _ => { panic!("{}: invalid index, confirm that Arena::insert() is configured properly", std::any::type_name::<Self>()) }
+        }
+    }
+
+    /// Detaches the entity from its parent and turns its children into roots.
+    pub fn remove(&mut self, id: ID) {
+        self.remove_parent(id);
+
+        let index = id.slot();
+        for child in std::mem::replace(&mut self.children[index], vec![]) {
+            self.parents[child.slot()] = None;
+        }
+        self.ids[index] = None;
+    }
+
+    pub fn parent(&self, id: ID) -> Option<ID> {
+        self.parents.get(id.slot()).and_then(|parent| *parent)
+    }
+
+    pub fn children(&self, id: ID) -> &[ID] {
+        self.children.get(id.slot())
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn is_ancestor(&self, ancestor: ID, id: ID) -> bool {
+        let mut current = self.parent(id);
+
+        while let Some(parent) = current {
+            if parent == ancestor {
+                return true;
+            }
+            current = self.parent(parent);
+        }
+
+        false
+    }
+
+    pub fn set_parent(&mut self, child: ID, parent: ID) -> std::result::Result<(), HierarchyError> {
+        if child == parent {
+            return Err(HierarchyError::SelfParent);
+        }
+
+        if self.is_ancestor(child, parent) {
+            return Err(HierarchyError::Cycle);
+        }
+
+        self.remove_parent(child);
+
+        self.parents[child.slot()] = Some(parent);
+        self.children[parent.slot()].push(child);
+
+        Ok(())
+    }
+
+    pub fn remove_parent(&mut self, child: ID) {
+        if let Some(parent) = self.parents[child.slot()].take() {
+            self.children[parent.slot()].retain(|c| *c != child);
+        }
+    }
+
+    pub fn roots<'a>(&'a self) -> impl Iterator<Item=ID> + 'a {
+        self.ids.iter()
+            .zip(self.parents.iter())
+            .filter_map(|(id, parent)| match parent {
+                Some(_) => None,
+                None => *id,
+            })
+    }
+
+    /// Visits the root and its descendants, each child subtree before its next sibling.
+    pub fn depth_first(&self, root: ID) -> DepthFirst<ID> {
+        DepthFirst {
+            hierarchy: self,
+            stack: vec![root],
+        }
+    }
+
+    /// Visits the root and its descendants, level by level.
+    pub fn breadth_first(&self, root: ID) -> BreadthFirst<ID> {
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+
+        BreadthFirst {
+            hierarchy: self,
+            queue,
+        }
+    }
+}
+
+impl<T> Hierarchy<GenId<T>> {
+    /// Moves the slots to follow a compacted allocator and updates the stored ids.
+    pub fn compact(&mut self, remap: &Remap<T>) {
+        for (old, new) in remap.moves() {
+            self.ids.swap(old, new);
+            self.parents.swap(old, new);
+            self.children.swap(old, new);
+        }
+
+        self.ids.truncate(remap.len());
+        self.parents.truncate(remap.len());
+        self.children.truncate(remap.len());
+
+        for id in self.ids.iter_mut() {
+            *id = id.and_then(|id| remap.get(id));
+        }
+
+        for parent in self.parents.iter_mut() {
+            *parent = parent.and_then(|parent| remap.get(parent));
+        }
+
+        for children in self.children.iter_mut() {
+            *children = children.iter()
+                .filter_map(|child| remap.get(*child))
+                .collect();
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DepthFirst<'a, ID> {
+    hierarchy: &'a Hierarchy<ID>,
+    stack: Vec<ID>,
+}
+
+impl<'a, ID: ArenaId> Iterator for DepthFirst<'a, ID> {
+    type Item = ID;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.stack.pop()?;
+        self.stack.extend(self.hierarchy.children(id).iter().rev());
+        Some(id)
+    }
+}
+
+#[derive(Debug)]
+pub struct BreadthFirst<'a, ID> {
+    hierarchy: &'a Hierarchy<ID>,
+    queue: VecDeque<ID>,
+}
+
+impl<'a, ID: ArenaId> Iterator for BreadthFirst<'a, ID> {
+    type Item = ID;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self.queue.pop_front()?;
+        self.queue.extend(self.hierarchy.children(id).iter());
+        Some(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocators::FixedAllocator;
+    use crate::ids::Id;
+
+    #[derive(Debug)]
+    struct Body;
+
+    fn solar_system() -> (Hierarchy<Id<Body>>, Vec<Id<Body>>) {
+        let mut allocator = FixedAllocator::<Body>::default();
+        let mut hierarchy = Hierarchy::new();
+
+        let ids: Vec<_> = (0..5).map(|_| allocator.create()).collect();
+        for id in ids.iter() {
+            hierarchy.insert(*id);
+        }
+
+        // sun -> earth -> moon, sun -> mars -> phobos
+        hierarchy.set_parent(ids[1], ids[0]).unwrap();
+        hierarchy.set_parent(ids[2], ids[1]).unwrap();
+        hierarchy.set_parent(ids[3], ids[0]).unwrap();
+        hierarchy.set_parent(ids[4], ids[3]).unwrap();
+
+        (hierarchy, ids)
+    }
+
+    #[test]
+    fn set_parent_links_both_sides() {
+        let (hierarchy, ids) = solar_system();
+
+        assert_eq!(Some(ids[0]), hierarchy.parent(ids[1]));
+        assert_eq!(&[ids[1], ids[3]], hierarchy.children(ids[0]));
+    }
+
+    #[test]
+    fn set_parent_moves_child() {
+        let (mut hierarchy, ids) = solar_system();
+
+        hierarchy.set_parent(ids[2], ids[3]).unwrap();
+
+        assert!(hierarchy.children(ids[1]).is_empty());
+        assert_eq!(&[ids[4], ids[2]], hierarchy.children(ids[3]));
+    }
+
+    #[test]
+    fn set_parent_prevents_cycles() {
+        let (mut hierarchy, ids) = solar_system();
+
+        assert_eq!(Err(HierarchyError::SelfParent), hierarchy.set_parent(ids[0], ids[0]));
+        assert_eq!(Err(HierarchyError::Cycle), hierarchy.set_parent(ids[0], ids[2]));
+    }
+
+    #[test]
+    fn roots_only_lists_parentless() {
+        let (hierarchy, ids) = solar_system();
+
+        assert_eq!(vec![ids[0]], hierarchy.roots().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn remove_orphans_children() {
+        let (mut hierarchy, ids) = solar_system();
+
+        hierarchy.remove(ids[1]);
+
+        assert_eq!(None, hierarchy.parent(ids[2]));
+        assert_eq!(&[ids[3]], hierarchy.children(ids[0]));
+        assert_eq!(vec![ids[0], ids[2]], hierarchy.roots().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn depth_first_order() {
+        let (hierarchy, ids) = solar_system();
+
+        let order: Vec<_> = hierarchy.depth_first(ids[0]).collect();
+
+        assert_eq!(vec![ids[0], ids[1], ids[2], ids[3], ids[4]], order);
+    }
+
+    #[test]
+    fn breadth_first_order() {
+        let (hierarchy, ids) = solar_system();
+
+        let order: Vec<_> = hierarchy.breadth_first(ids[0]).collect();
+
+        assert_eq!(vec![ids[0], ids[1], ids[3], ids[2], ids[4]], order);
+    }
+}
@@ -4,6 +4,7 @@ use std::cmp::Ordering;
 use std::num::NonZeroU32;
 use std::fmt::{Display, Formatter, Result};
 use std::marker::PhantomData;
+use std::str::FromStr;
 use code_gen::Type;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -20,6 +21,13 @@ impl Allocator {
         }.parse().unwrap()
     }
 
+    pub fn get_reservations_type(self, arena: &Arena) -> Type {
+        match self {
+            Allocator::Fixed => format!("FixedReservations<{}>", arena.name),
+            Allocator::Generational => format!("GenReservations<{}>", arena.name),
+        }.parse().unwrap()
+    }
+
     pub fn get_id_type(self, arena: &Arena) -> Type {
         let s = match self {
             Allocator::Fixed => format!("Id<{}>", arena.name),
@@ -54,6 +62,14 @@ impl Generation {
         Generation(next_gen)
     }
 
+    /// Returns None once the generation cannot be incremented without wrapping.
+    pub fn checked_next(self) -> Option<Self> {
+        self.0.get()
+            .checked_add(1)
+            .and_then(NonZeroU32::new)
+            .map(Generation)
+    }
+
     pub fn value(self) -> u32 {
         self.0.get()
     }
@@ -122,6 +138,25 @@ impl<T> Id<T> {
     pub fn id(&self) -> Self {
         *self
     }
+
+    /// The index of the id, for save files and network messages. Convert back with `FixedAllocator::resolve`.
+    pub fn to_usize(self) -> usize {
+        self.index
+    }
+
+    pub fn to_u64(self) -> u64 {
+        self.index as u64
+    }
+}
+
+/// Parses the index from the `Display` form of an `Id`, e.g. `Id(3)`.
+pub (crate) fn parse_id_index(s: &str) -> Option<usize> {
+    s.trim()
+        .strip_prefix("Id(")?
+        .strip_suffix(')')?
+        .trim()
+        .parse()
+        .ok()
 }
 
 #[derive(Debug)]
@@ -151,6 +186,64 @@ impl<T> GenId<T> {
             gen,
         }
     }
+
+    /// Packs the generation into the high 32 bits and the index into the low 32 bits.
+    /// Returns None if the index does not fit in 32 bits.
+    pub fn to_u64(self) -> Option<u64> {
+        if self.id.index > u32::MAX as usize {
+            return None;
+        }
+
+        Some(u64::from(self.gen.value()) << 32 | self.id.index as u64)
+    }
+
+    /// Unpacks an id from `to_u64`. The id must still be checked with `GenAllocator::verify`.
+    pub fn from_u64(value: u64) -> Option<Self> {
+        let gen = NonZeroU32::new((value >> 32) as u32)?;
+        let index = (value & u64::from(u32::MAX)) as usize;
+
+        Some(Self::new(index, Generation(gen)))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParseIdError;
+
+impl Display for ParseIdError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "invalid id")
+    }
+}
+
+impl std::error::Error for ParseIdError {}
+
+/// Parses the `Display` form of a `GenId`, e.g. `GenId(3,1)`. The id must still be checked with `GenAllocator::verify`.
+impl<T> FromStr for GenId<T> {
+    type Err = ParseIdError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let inner = s.trim()
+            .strip_prefix("GenId(")
+            .and_then(|s| s.strip_suffix(')'))
+            .ok_or(ParseIdError)?;
+
+        let mut parts = inner.split(',').map(str::trim);
+
+        let index = parts.next()
+            .and_then(|index| index.parse::<usize>().ok())
+            .ok_or(ParseIdError)?;
+
+        let gen = parts.next()
+            .and_then(|gen| gen.parse::<u32>().ok())
+            .and_then(NonZeroU32::new)
+            .ok_or(ParseIdError)?;
+
+        if parts.next().is_some() {
+            return Err(ParseIdError);
+        }
+
+        Ok(Self::new(index, Generation(gen)))
+    }
 }
 
 impl<T> PartialEq for GenId<T> {
@@ -179,6 +272,40 @@ impl<T> Ord for GenId<T> {
     }
 }
 
+/// Implemented by the id types stored in link columns, giving the slot they occupy in their arena.
+pub trait ArenaId: Copy + Eq {
+    fn slot(&self) -> usize;
+}
+
+impl<T> ArenaId for Id<T> {
+    fn slot(&self) -> usize {
+        self.index
+    }
+}
+
+impl<T> ArenaId for GenId<T> {
+    fn slot(&self) -> usize {
+        self.id.index
+    }
+}
+
+/// Implemented by the id types that can index a column without checking the allocator.
+pub trait ValidId<T> {
+    fn index(&self) -> usize;
+}
+
+impl<T> ValidId<T> for Id<T> {
+    fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<T> ValidId<T> for Valid<T> {
+    fn index(&self) -> usize {
+        self.id.id.index
+    }
+}
+
 #[derive(Debug)]
 pub struct Valid<T> {
     pub (crate) id: GenId<T>,
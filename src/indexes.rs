@@ -0,0 +1,246 @@
+use crate::allocators::Remap;
+use crate::ids::GenId;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::fmt::{Debug, Display, Formatter, Result};
+
+/// Maps each value of an indexed component to the single entity holding it.
+#[derive(Debug, Clone)]
+pub struct UniqueIndex<ID, K> {
+    ids: HashMap<K, ID>,
+    keys: HashMap<ID, K>,
+}
+
+impl<ID: Hash + Eq, K: Hash + Eq> Default for UniqueIndex<ID, K> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl<ID: Copy + Hash + Eq, K: Clone + Hash + Eq> UniqueIndex<ID, K> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn get(&self, key: &K) -> Option<ID> {
+        self.ids.get(key).copied()
+    }
+
+    /// Returns the entity already holding the key, if it is not the given id.
+    pub fn conflict(&self, key: &K, id: &ID) -> Option<ID> {
+        self.get(key).filter(|existing| existing != id)
+    }
+
+    pub fn insert(&mut self, id: ID, key: K) {
+        self.remove(&id);
+
+        self.keys.insert(id, key.clone());
+        if let Some(previous) = self.ids.insert(key, id) {
+            if previous != id {
+                self.keys.remove(&previous);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: &ID) {
+        if let Some(key) = self.keys.remove(id) {
+            self.ids.remove(&key);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+impl<T, K: Clone + Hash + Eq> UniqueIndex<GenId<T>, K> {
+    /// Updates the index to follow a compacted allocator.
+    pub fn compact(&mut self, remap: &Remap<T>) {
+        let keys = std::mem::replace(&mut self.keys, HashMap::new());
+        self.ids.clear();
+
+        for (id, key) in keys {
+            if let Some(new) = remap.get(id) {
+                self.insert(new, key);
+            }
+        }
+    }
+}
+
+/// Maps each value of an indexed component to every entity holding it.
+#[derive(Debug, Clone)]
+pub struct MultiIndex<ID, K> {
+    ids: HashMap<K, Vec<ID>>,
+    keys: HashMap<ID, K>,
+}
+
+impl<ID: Hash + Eq, K: Hash + Eq> Default for MultiIndex<ID, K> {
+    fn default() -> Self {
+        Self {
+            ids: HashMap::new(),
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl<ID: Copy + Hash + Eq, K: Clone + Hash + Eq> MultiIndex<ID, K> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn get(&self, key: &K) -> &[ID] {
+        self.ids.get(key)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn insert(&mut self, id: ID, key: K) {
+        self.remove(&id);
+
+        self.ids.entry(key.clone()).or_insert_with(Vec::new).push(id);
+        self.keys.insert(id, key);
+    }
+
+    pub fn remove(&mut self, id: &ID) {
+        if let Some(key) = self.keys.remove(id) {
+            if let Some(ids) = self.ids.get_mut(&key) {
+                ids.retain(|i| i != id);
+
+                if ids.is_empty() {
+                    self.ids.remove(&key);
+                }
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl<T, K: Clone + Hash + Eq> MultiIndex<GenId<T>, K> {
+    /// Updates the index to follow a compacted allocator.
+    pub fn compact(&mut self, remap: &Remap<T>) {
+        let keys = std::mem::replace(&mut self.keys, HashMap::new());
+        self.ids.clear();
+
+        for (id, key) in keys {
+            if let Some(new) = remap.get(id) {
+                self.insert(new, key);
+            }
+        }
+    }
+}
+
+/// Returned by generated `insert` and `create` when a unique component value is already taken.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DuplicateKey<ID> {
+    pub component: &'static str,
+    pub existing: ID,
+}
+
+impl<ID> DuplicateKey<ID> {
+    pub fn new(component: &'static str, existing: ID) -> Self {
+        Self {
+            component,
+            existing,
+        }
+    }
+}
+
+impl<ID: Display> Display for DuplicateKey<ID> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "duplicate value for unique component '{}', already used by {}", self.component, self.existing)
+    }
+}
+
+impl<ID: Debug + Display> std::error::Error for DuplicateKey<ID> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_insert_and_get() {
+        let mut index = UniqueIndex::<usize, &str>::new();
+
+        index.insert(0, "Sol");
+        index.insert(1, "Alpha Centauri");
+
+        assert_eq!(Some(0), index.get(&"Sol"));
+        assert_eq!(Some(1), index.get(&"Alpha Centauri"));
+        assert_eq!(None, index.get(&"Vega"));
+    }
+
+    #[test]
+    fn unique_reinsert_replaces_old_key() {
+        let mut index = UniqueIndex::<usize, &str>::new();
+
+        index.insert(0, "Sol");
+        index.insert(0, "Sun");
+
+        assert_eq!(None, index.get(&"Sol"));
+        assert_eq!(Some(0), index.get(&"Sun"));
+        assert_eq!(1, index.len());
+    }
+
+    #[test]
+    fn unique_conflict_ignores_own_id() {
+        let mut index = UniqueIndex::<usize, &str>::new();
+
+        index.insert(0, "Sol");
+
+        assert_eq!(None, index.conflict(&"Sol", &0));
+        assert_eq!(Some(0), index.conflict(&"Sol", &1));
+        assert_eq!(None, index.conflict(&"Vega", &1));
+    }
+
+    #[test]
+    fn unique_remove() {
+        let mut index = UniqueIndex::<usize, &str>::new();
+
+        index.insert(0, "Sol");
+        index.remove(&0);
+
+        assert_eq!(None, index.get(&"Sol"));
+        assert!(index.is_empty());
+    }
+
+    #[test]
+    fn multi_insert_and_remove() {
+        let mut index = MultiIndex::<usize, &str>::new();
+
+        index.insert(0, "rocky");
+        index.insert(1, "gas giant");
+        index.insert(2, "rocky");
+
+        assert_eq!(&[0, 2], index.get(&"rocky"));
+
+        index.remove(&0);
+
+        assert_eq!(&[2], index.get(&"rocky"));
+        assert!(index.get(&"ice giant").is_empty());
+    }
+
+    #[test]
+    fn multi_reinsert_moves_id() {
+        let mut index = MultiIndex::<usize, &str>::new();
+
+        index.insert(0, "rocky");
+        index.insert(0, "gas giant");
+
+        assert!(index.get(&"rocky").is_empty());
+        assert_eq!(&[0], index.get(&"gas giant"));
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt::{Display, Formatter, Result};
+use crate::ids::ArenaId;
+
+/// A broken invariant found by the generated `World::check_integrity`. Columns are named `arena.column`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum IntegrityError {
+    /// The column does not hold one value per slot of the arena's allocator.
+    ColumnLength { column: &'static str, expected: usize, actual: usize },
+    /// The link of the entity at `index` points at an entity that is dead or was never created.
+    DeadLink { column: &'static str, index: usize },
+    /// The required ownership link of the entity at `index` points at an entity that does not exist.
+    MissingOwned { column: &'static str, index: usize },
+    /// The entity at `index` is linked to an entity that does not link back to it.
+    OneSidedLink { column: &'static str, index: usize },
+    /// The relation or backlink table pairs the dead entity at `index` with the entities it was related to.
+    DeadSource { table: &'static str, index: usize },
+    /// The relation or backlink table relates entities to the dead entity at `index`.
+    DeadTarget { table: &'static str, index: usize },
+}
+
+impl Display for IntegrityError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            IntegrityError::ColumnLength { column, expected, actual } => write!(f, "{}: expected {} values, found {}", column, expected, actual),
+            IntegrityError::DeadLink { column, index } => write!(f, "{}[{}]: linked entity is not alive", column, index),
+            IntegrityError::MissingOwned { column, index } => write!(f, "{}[{}]: owned entity does not exist", column, index),
+            IntegrityError::OneSidedLink { column, index } => write!(f, "{}[{}]: linked entity does not link back", column, index),
+            IntegrityError::DeadSource { table, index } => write!(f, "{}: source {} is not alive", table, index),
+            IntegrityError::DeadTarget { table, index } => write!(f, "{}: target {} is not alive", table, index),
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
+
+impl IntegrityError {
+    pub fn check_len(column: &'static str, expected: usize, actual: usize) -> Option<Self> {
+        if expected == actual {
+            None
+        } else {
+            Some(IntegrityError::ColumnLength { column, expected, actual })
+        }
+    }
+
+    /// The errors for a pair of a relation or backlink table, one for each side that is not alive.
+    pub fn check_pair<A: ArenaId, B: ArenaId>(table: &'static str, a: A, a_alive: bool, b: B, b_alive: bool) -> impl Iterator<Item=Self> {
+        let source = Some(IntegrityError::DeadSource { table, index: a.slot() }).filter(|_| !a_alive);
+        let target = Some(IntegrityError::DeadTarget { table, index: b.slot() }).filter(|_| !b_alive);
+
+        source.into_iter().chain(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_len_reports_mismatch() {
+        assert_eq!(None, IntegrityError::check_len("body.name", 2, 2));
+
+        let error = IntegrityError::check_len("body.name", 2, 1).unwrap();
+
+        assert_eq!(IntegrityError::ColumnLength { column: "body.name", expected: 2, actual: 1 }, error);
+        assert_eq!("body.name: expected 2 values, found 1", error.to_string());
+    }
+
+    #[test]
+    fn check_pair_reports_each_dead_side() {
+        use crate::ids::Id;
+
+        let (a, b) = (Id::<u8>::new(1), Id::<u16>::new(2));
+
+        assert_eq!(0, IntegrityError::check_pair("fleet", a, true, b, true).count());
+        assert_eq!(
+            vec![IntegrityError::DeadSource { table: "fleet", index: 1 }, IntegrityError::DeadTarget { table: "fleet", index: 2 }],
+            IntegrityError::check_pair("fleet", a, false, b, false).collect::<Vec<_>>(),
+        );
+    }
+}
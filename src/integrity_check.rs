@@ -0,0 +1,186 @@
+use crate::*;
+use code_gen::{CamelCase, SnakeCase, Function, CodeLine};
+
+// World::check_integrity walks every arena and reports the invariants that the generated code is
+// meant to maintain: columns hold one value per allocator slot, links point at entities that exist,
+// two-way links agree, required ownership links have their owned entity, and relation and backlink
+// tables only hold living entities. It is meant for debug builds and fuzz tests, as it visits every
+// link of every living entity and every pair of every table.
+
+/// A line of generated code, with its indentation relative to the enclosing block.
+type Line = (usize, String);
+
+pub fn get_check_integrity(world: &World) -> Function {
+    let mut lines = vec![(0, String::from("let mut errors = vec![];"))];
+
+    for arena in world.arenas.iter() {
+        lines.extend(get_length_checks(world, arena));
+    }
+
+    for arena in world.arenas.iter() {
+        lines.extend(get_link_checks(world, arena));
+    }
+
+    for relation in world.relations.iter() {
+        lines.extend(get_table_checks(world, &relation.name, &relation.from, &relation.to));
+    }
+
+    for backlink in get_backlinks(world) {
+        lines.extend(get_table_checks(world, &backlink.get_name(), &backlink.from, &backlink.to));
+    }
+
+    lines.push((0, String::from("errors")));
+
+    let mut f = Function::new("check_integrity")
+        .with_parameters("&self")
+        .with_return(String::from("Vec<IntegrityError>"));
+
+    for (indent, line) in lines {
+        f = f.add_line(CodeLine::new(indent, &line));
+    }
+
+    f
+}
+
+/// Tag columns are bitsets without a value per slot, so their lengths are not checked.
+fn get_length_checks(world: &World, arena: &Arena) -> Vec<Line> {
+    let field = arena.get_state_field().name;
+
+    arena.get_columns(world)
+        .into_iter()
+        .filter(|column| column.storage != Storage::Tag)
+        .map(|column| (0, format!(
+            "errors.extend(IntegrityError::check_len(\"{f}.{c}\", self.allocators.{f}.len(), self.state.{f}.{c}.len()));",
+            f=field,
+            c=column.name,
+        )))
+        .collect()
+}
+
+fn get_link_checks(world: &World, arena: &Arena) -> Vec<Line> {
+    let field = arena.get_state_field().name;
+
+    let mut links: Vec<_> = arena.ownership.iter()
+        .map(|(link_to, link_type)| (link_to, link_type, true))
+        .chain(arena.references.iter().map(|(link_to, link_type)| (link_to, link_type, false)))
+        .collect();
+    links.sort_by_key(|(link_to, _, _)| link_to.to_string());
+
+    if links.is_empty() {
+        return vec![];
+    }
+
+    let mut lines = match arena.allocator {
+        Allocator::Fixed => vec![
+            (0, format!("for id in self.allocators.{}.ids() {{", field)),
+            (1, String::from("let valid = &id;")),
+        ],
+        Allocator::Generational => vec![
+            (0, format!("for valid in self.allocators.{}.ids() {{", field)),
+        ],
+    };
+
+    for (link_to, link_type, owns) in links {
+        let to = world.get_arena(link_to);
+        let to_field = to.get_state_field().name;
+        let column = format!("{}.{}", field, to_field);
+
+        let dead = if owns && *link_type == LinkType::Required { "MissingOwned" } else { "DeadLink" };
+
+        let resolve = match to.allocator {
+            Allocator::Fixed => format!("self.allocators.{}.resolve(linked.to_usize()).as_ref()", to_field),
+            Allocator::Generational => format!("self.allocators.{}.verify(linked)", to_field),
+        };
+
+        let linked = get_linked_id(arena, &to.name, *link_type, &format!("self.state.{}[valid]", column));
+        let dead = format!("errors.push(IntegrityError::{} {{ column: \"{}\", index: valid.index() }});", dead, column);
+
+        lines.push((1, format!("if let Some(linked) = {} {{", linked)));
+
+        // two-way links are checked from both sides, so each side only checks that it is linked back
+        match to.references.get(&arena.name) {
+            Some(back_type) => {
+                let back = get_linked_id(to, &arena.name, *back_type, &format!("self.state.{}.{}[linked]", to_field, field));
+
+                lines.push((2, format!("if let Some(linked) = {} {{", resolve)));
+                lines.push((3, format!("if {} != Some(valid.id()) {{", back)));
+                lines.push((4, format!("errors.push(IntegrityError::OneSidedLink {{ column: \"{}\", index: valid.index() }});", column)));
+                lines.push((3, String::from("}")));
+                lines.push((2, String::from("} else {")));
+                lines.push((3, dead));
+                lines.push((2, String::from("}")));
+            },
+            None => {
+                lines.push((2, format!("if {}.is_none() {{", resolve)));
+                lines.push((3, dead));
+                lines.push((2, String::from("}")));
+            },
+        }
+
+        lines.push((1, String::from("}")));
+    }
+
+    lines.push((0, String::from("}")));
+    lines
+}
+
+/// Every pair of the relation or backlink table relates two living entities.
+fn get_table_checks(world: &World, table: &SnakeCase, from: &CamelCase, to: &CamelCase) -> Vec<Line> {
+    let alive = |arena: &CamelCase, id: &str| {
+        let arena = world.get_arena(arena);
+        let field = arena.get_state_field().name;
+
+        match arena.allocator {
+            Allocator::Fixed => format!("self.allocators.{}.resolve({}.to_usize()).is_some()", field, id),
+            Allocator::Generational => format!("self.allocators.{}.is_alive({})", field, id),
+        }
+    };
+
+    vec![
+        (0, format!("for (a, b) in self.state.{}.iter() {{", table)),
+        (1, format!(
+            "errors.extend(IntegrityError::check_pair(\"{}\", a, {}, b, {}));",
+            table,
+            alive(from, "a"),
+            alive(to, "b"),
+        )),
+        (0, String::from("}")),
+    ]
+}
+
+/// Converts a value of a link column into the `Option` of the linked id, dropping any edge data.
+fn get_linked_id(arena: &Arena, link_to: &CamelCase, link_type: LinkType, value: &str) -> String {
+    let edged = arena.get_edge_type(link_to).is_some();
+
+    match (link_type, edged) {
+        (LinkType::Required, false) => format!("Some({})", value),
+        (LinkType::Required, true) => format!("Some({}.0)", value),
+        (LinkType::Optional, false) => value.to_string(),
+        (LinkType::Optional, true) => format!("{}.as_ref().map(|(id, _)| *id)", value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_integrity_visits_relation_and_backlink_tables() {
+        let controller = Arena::generational("Controller");
+        let fleet = Arena::fixed("Fleet");
+        let mut ship = Arena::generational("Ship");
+        ship.add_reference(&controller, LinkType::Optional);
+
+        let world = World::new()
+            .add_arena(controller.clone())
+            .add_arena(fleet.clone())
+            .add_arena(ship.clone())
+            .add_relation(Relation::new("escorts", &ship, &fleet));
+
+        let check = world.impl_world().to_string();
+
+        assert!(check.contains("for (a, b) in self.state.escorts.iter() {"));
+        assert!(check.contains("errors.extend(IntegrityError::check_pair(\"escorts\", a, self.allocators.ship.is_alive(a), b, self.allocators.fleet.resolve(b.to_usize()).is_some()));"));
+        assert!(check.contains("for (a, b) in self.state.ship_controller_backlinks.iter() {"));
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::{Debug, Formatter, Result};
+use std::sync::Arc;
+
+struct Entry<W> {
+    undo: Arc<dyn Fn(&mut W) + Send + Sync>,
+    redo: Arc<dyn Fn(&mut W) + Send + Sync>,
+}
+
+impl<W> Clone for Entry<W> {
+    fn clone(&self) -> Self {
+        Self {
+            undo: self.undo.clone(),
+            redo: self.redo.clone(),
+        }
+    }
+}
+
+/// Invertible operations on a world `W`, recorded by the journaled functions of the generated `World`.
+/// Mutations made without the journal are not tracked, so undoing past them can leave the world inconsistent.
+/// The recorded operations are `Send + Sync`, so a journaled world can still be moved between threads.
+pub struct Journal<W> {
+    done: Vec<Entry<W>>,
+    undone: Vec<Entry<W>>,
+}
+
+impl<W> Default for Journal<W> {
+    fn default() -> Self {
+        Self {
+            done: vec![],
+            undone: vec![],
+        }
+    }
+}
+
+impl<W> Clone for Journal<W> {
+    fn clone(&self) -> Self {
+        Self {
+            done: self.done.clone(),
+            undone: self.undone.clone(),
+        }
+    }
+}
+
+impl<W> Debug for Journal<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "Journal({} done, {} undone)", self.done.len(), self.undone.len())
+    }
+}
+
+impl<W> Journal<W> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Records an operation that has just been applied. Operations that were undone can no longer be redone.
+    pub fn record<U, R>(&mut self, undo: U, redo: R)
+        where U: Fn(&mut W) + Send + Sync + 'static,
+              R: Fn(&mut W) + Send + Sync + 'static,
+    {
+        self.undone.clear();
+        self.done.push(Entry {
+            undo: Arc::new(undo),
+            redo: Arc::new(redo),
+        });
+    }
+
+    /// Reverts the latest operation, returning false if there is none.
+    pub fn undo(&mut self, world: &mut W) -> bool {
+        match self.done.pop() {
+            Some(entry) => {
+                (entry.undo)(world);
+                self.undone.push(entry);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Reapplies the latest undone operation, returning false if there is none.
+    pub fn redo(&mut self, world: &mut W) -> bool {
+        match self.undone.pop() {
+            Some(entry) => {
+                (entry.redo)(world);
+                self.done.push(entry);
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.done.clear();
+        self.undone.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(journal: &mut Journal<Vec<u32>>, world: &mut Vec<u32>, value: u32) {
+        world.push(value);
+        journal.record(
+            |w: &mut Vec<u32>| { w.pop(); },
+            move |w: &mut Vec<u32>| w.push(value),
+        );
+    }
+
+    #[test]
+    fn undo_and_redo() {
+        let mut journal = Journal::new();
+        let mut world = vec![];
+
+        push(&mut journal, &mut world, 1);
+        push(&mut journal, &mut world, 2);
+
+        assert!(journal.undo(&mut world));
+        assert_eq!(vec![1], world);
+
+        assert!(journal.redo(&mut world));
+        assert_eq!(vec![1, 2], world);
+
+        assert!(!journal.redo(&mut world));
+    }
+
+    #[test]
+    fn journal_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<Journal<Vec<u32>>>();
+    }
+
+    #[test]
+    fn record_clears_redo() {
+        let mut journal = Journal::new();
+        let mut world = vec![];
+
+        push(&mut journal, &mut world, 1);
+        journal.undo(&mut world);
+        push(&mut journal, &mut world, 3);
+
+        assert!(!journal.can_redo());
+
+        journal.undo(&mut world);
+        journal.undo(&mut world);
+
+        assert!(world.is_empty());
+        assert!(!journal.can_undo());
+    }
+}
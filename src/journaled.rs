@@ -0,0 +1,693 @@
+use crate::*;
+use code_gen::*;
+
+// Journaled functions mirror the mutations of the generated World and record how to invert them
+// in World::journal, so that World::undo and World::redo can step through them. Undoing a create
+// frees the id for good, and redoing it brings back the same GenId. Undoing a kill revives the same
+// GenId with every column it had, including the kept values of histories, and returns it to its
+// place in the hierarchy. Compacting an arena clears the journal.
+
+const EXPECT_UNIQUE: &str = ".expect(\"Journal: unique component value is already in use\")";
+const EXPECT_HIERARCHY: &str = ".expect(\"Journal: hierarchy could not be restored\")";
+
+/// A line of generated code, with its indentation relative to the enclosing block.
+type Line = (usize, String);
+
+pub fn get_entity_struct(world: &World, arena: &Arena) -> Struct {
+    let row = Field {
+        visibility: Default::default(),
+        name: "row".parse().unwrap(),
+        field_type: arena.get_data_row().typ,
+    };
+
+    // the row and the columns hold only the latest value of a history, so every kept value is stored as well
+    let histories = arena.history_components().map(|component| Field {
+        visibility: Default::default(),
+        name: get_history_field(component),
+        field_type: Type::new(&format!("Vec<{}>", component.data_type)),
+    });
+
+    let fields = std::iter::once(row)
+        .chain(arena.get_non_row_columns(world).iter().map(ComponentType::get_data_field))
+        .chain(histories)
+        .collect();
+
+    Struct::new(&format!("{}Entity", arena.name))
+        .with_derives(Derives::with_debug_clone())
+        .with_fields(fields)
+}
+
+/// `get_entity` and `set_entity`, which read and write every column of one entity.
+pub fn impl_entity(world: &World, arena: &Arena) -> Impl {
+    let entity = format!("{}Entity", arena.name);
+    let id = arena.get_valid_id_type_self();
+    let columns = arena.get_non_row_columns(world);
+
+    let mut get_entity = Function::new("get_entity")
+        .with_parameters(&format!("&self, id: &{}", id))
+        .with_return(entity.clone())
+        .add_line(CodeLine::new(0, &format!("{} {{", entity)))
+        .add_line(CodeLine::new(1, "row: self.get_row(id),"));
+
+    for column in columns.iter() {
+        get_entity = get_entity.add_line(CodeLine::new(1, &format!("{name}: self.{name}[id].clone(),", name=column.name)));
+    }
+
+    for component in arena.history_components() {
+        get_entity = get_entity.add_line(CodeLine::new(1, &format!(
+            "{}: self.{}.iter_history(id).cloned().collect(),",
+            get_history_field(component),
+            component.name,
+        )));
+    }
+
+    let mut set_entity = Function::new("set_entity")
+        .with_parameters(&format!("&mut self, id: &{}, entity: {}", id, entity));
+
+    for line in arena.get_row_writes("entity.row") {
+        set_entity = set_entity.add_line(line);
+    }
+
+    for column in columns.iter() {
+        set_entity = set_entity.add_line(CodeLine::new(0, &format!("self.{name}.insert(id, entity.{name});", name=column.name)));
+    }
+
+    for component in arena.history_components() {
+        set_entity = set_entity.add_line(CodeLine::new(0, &format!(
+            "self.{}.set_history(id, entity.{});",
+            component.name,
+            get_history_field(component),
+        )));
+    }
+
+    Impl::from(&arena.get_arena_type())
+        .add_function(get_entity.add_line(CodeLine::new(0, "}")))
+        .add_function(set_entity)
+}
+
+fn get_history_field(component: &ComponentType) -> SnakeCase {
+    format!("{}_history", component.name).parse().unwrap()
+}
+
+pub fn get_journaled_functions(world: &World) -> Vec<Function> {
+    let mut functions = vec![get_undo("undo"), get_undo("redo")];
+
+    for arena in world.arenas.iter() {
+        functions.push(get_create(arena));
+        functions.push(get_insert(arena));
+
+        if arena.allocator == Allocator::Generational {
+            functions.push(get_kill(world, arena));
+        }
+
+        // indexes are only generated for the components of the data row
+        let setters = arena.components.iter()
+            .map(|c| (c, c.index.is_some()))
+            .chain(arena.default_components.iter().map(|c| (c, false)))
+            .filter(|(c, _)| match c.storage {
+                Storage::Linear | Storage::LinearOption | Storage::Tag | Storage::DoubleBuffered => true,
+                Storage::History(_) => false,
+            });
+
+        for (component, indexed) in setters {
+            functions.push(get_set(arena, component, indexed));
+        }
+    }
+
+    let mut links: Vec<_> = world.links.iter()
+        .filter(|(link, _)| link.is_implemented(world))
+        .collect();
+    links.sort_by_key(|(link, _)| (link.from.to_string(), link.to.to_string()));
+
+    for (link, link_type) in links {
+        functions.push(get_link(world, link, "Link"));
+
+        if link.is_unlinkable(world, link_type) {
+            functions.push(get_link(world, link, "Unlink"));
+        }
+    }
+
+    for relation in world.relations.iter() {
+        functions.push(get_relate(world, relation, "relate", "insert", "remove"));
+        functions.push(get_relate(world, relation, "unrelate", "remove", "insert"));
+    }
+
+    functions
+}
+
+fn get_undo(name: &str) -> Function {
+    Function::new(name)
+        .with_parameters("&mut self")
+        .with_return(String::from("bool"))
+        .add_line(CodeLine::new(0, "let mut journal = std::mem::take(&mut self.journal);"))
+        .add_line(CodeLine::new(0, &format!("let done = journal.{}(self);", name)))
+        .add_line(CodeLine::new(0, "self.journal = journal;"))
+        .add_line(CodeLine::new(0, "done"))
+}
+
+fn get_create(arena: &Arena) -> Function {
+    let field = arena.get_state_field().name;
+    let fallible = arena.get_insert_error();
+
+    let create = match fallible {
+        Some(_) => format!("let id = self.state.{f}.create(row.clone(), &mut self.allocators.{f})?;", f=field),
+        None => format!("let id = self.state.{f}.create(row.clone(), &mut self.allocators.{f});", f=field),
+    };
+
+    let mut undo = vec![];
+    if arena.has_release() {
+        undo.extend(with_valid_ids("world", &[(arena, "id", "valid")], vec![
+            (0, format!("world.state.{}.release(valid);", field)),
+        ]));
+    }
+    undo.push((0, format!("world.allocators.{}.uncreate(id);", field)));
+    undo.extend(get_push_event(arena, "Killed"));
+
+    let reserved = match arena.allocator {
+        Allocator::Fixed => format!("let valid = &world.allocators.{}.create_reserved(id);", field),
+        Allocator::Generational => format!("let valid = world.allocators.{}.recreate(id);", field),
+    };
+
+    let mut redo = vec![
+        (0, reserved),
+        (0, format!("world.state.{}.insert(valid, row.clone()){};", field, if fallible.is_some() { EXPECT_UNIQUE } else { "" })),
+    ];
+    redo.extend(get_push_event(arena, "Created"));
+
+    let mut lines = vec![(0, create)];
+    lines.extend(record(undo, redo));
+
+    let f = Function::new(&format!("create_{}", field))
+        .with_parameters(&format!("&mut self, row: {}", arena.get_data_row().typ));
+
+    match fallible {
+        Some(error) => {
+            lines.push((0, String::from("Ok(id)")));
+            add_lines(f.with_return(format!("Result<{}, {}>", arena.get_id_type(), error)), lines)
+        },
+        None => {
+            lines.push((0, String::from("id")));
+            add_lines(f.with_return(arena.get_id_type().to_string()), lines)
+        },
+    }
+}
+
+fn get_insert(arena: &Arena) -> Function {
+    let field = arena.get_state_field().name;
+    let fallible = arena.get_insert_error();
+    let question = match fallible {
+        Some(_) => "?",
+        None => "",
+    };
+
+    // inserting resets the default components and links, and makes the entity a root of the hierarchy
+    let mut restore = vec![
+        (0, format!("world.state.{}.set_entity(valid, before.clone());", field)),
+    ];
+
+    let mut body = vec![
+        (0, format!("let before = self.state.{}.get_entity(valid);", field)),
+    ];
+
+    if arena.hierarchy {
+        body.extend(get_hierarchy_capture(arena));
+        restore.extend(get_hierarchy_restore(arena));
+    }
+
+    let undo = with_valid_ids("world", &[(arena, "id", "valid")], restore);
+
+    let redo = with_valid_ids("world", &[(arena, "id", "valid")], vec![
+        (0, format!("world.state.{}.insert(valid, row.clone()){};", field, if fallible.is_some() { EXPECT_UNIQUE } else { "" })),
+    ]);
+
+    body.push((0, format!("self.state.{}.insert(valid, row.clone()){};", field, question)));
+    body.extend(record(undo, redo));
+
+    let mut lines = with_valid_ids("self", &[(arena, "id", "valid")], body);
+
+    let mut f = Function::new(&format!("insert_{}", field))
+        .with_parameters(&format!("&mut self, id: {}, row: {}", arena.get_id_type(), arena.get_data_row().typ));
+
+    if let Some(error) = fallible {
+        f = f.with_return(format!("Result<(), {}>", error));
+        lines.push((0, String::from("Ok(())")));
+    }
+
+    add_lines(f, lines)
+}
+
+fn get_kill(world: &World, arena: &Arena) -> Function {
+    let field = arena.get_state_field().name;
+    let relations = world.relations.iter()
+        .filter(|relation| relation.involves(arena))
+        .collect::<Vec<_>>();
+
+    let mut undo = vec![
+        (0, format!("let valid = world.allocators.{}.revive(id);", field)),
+        (0, format!("world.state.{}.set_entity(valid, entity.clone());", field)),
+    ];
+
+    let mut body = vec![
+        (0, format!("let entity = self.state.{}.get_entity(valid);", field)),
+    ];
+
+    if arena.hierarchy {
+        body.extend(get_hierarchy_capture(arena));
+        undo.push((0, format!("world.state.{}.hierarchy.insert(id);", field)));
+        undo.extend(get_hierarchy_restore(arena));
+    }
+
+    undo.extend(get_push_event(arena, "Created"));
+
+    for relation in relations {
+        let name = &relation.name;
+
+        if relation.from == arena.name {
+            body.push((0, format!("let {n}_targets = self.state.{n}.targets(id).to_vec();", n=name)));
+            undo.push((0, format!("for b in {}_targets.iter() {{", name)));
+            undo.push((1, format!("world.state.{}.insert(id, *b);", name)));
+            undo.push((0, String::from("}")));
+        }
+
+        if relation.to == arena.name {
+            body.push((0, format!("let {n}_sources = self.state.{n}.sources(id).to_vec();", n=name)));
+            undo.push((0, format!("for a in {}_sources.iter() {{", name)));
+            undo.push((1, format!("world.state.{}.insert(*a, id);", name)));
+            undo.push((0, String::from("}")));
+        }
+    }
+
+    for backlink in get_backlinks(world) {
+        if backlink.from == arena.name {
+            undo.extend(backlink.get_track_lines(world, "world.state", "id", "valid"));
+        }
+
+        if backlink.to == arena.name {
+            let (name, column) = (backlink.get_name(), backlink.get_column());
+            let linking = world.get_arena(&backlink.from);
+
+            // the kill clears the links pointing at the entity, so undo restores them
+            let capture = match linking.allocator {
+                Allocator::Fixed => format!(".map(|linking| (*linking, self.state.{}[linking].clone()))", column),
+                Allocator::Generational => format!(
+                    ".filter_map(|linking| self.allocators.{}.verify(*linking).map(|valid| (*linking, self.state.{}[valid].clone())))",
+                    linking.get_state_field().name,
+                    column,
+                ),
+            };
+
+            body.push((0, format!("let {n} = self.state.{n}.sources(id).iter(){c}.collect::<Vec<_>>();", n=name, c=capture)));
+            undo.push((0, format!("for (linking, link) in {}.iter() {{", name)));
+
+            let indent = match linking.allocator {
+                Allocator::Fixed => {
+                    undo.push((1, String::from("let linking_valid = linking;")));
+                    1
+                },
+                Allocator::Generational => {
+                    undo.push((1, format!("if let Some(linking_valid) = world.allocators.{}.verify(*linking) {{", linking.get_state_field().name)));
+                    2
+                },
+            };
+
+            undo.push((indent, format!("world.state.{}.insert(linking_valid, link.clone());", column)));
+            undo.push((indent, format!("world.state.{}.insert(*linking, id);", name)));
+
+            if linking.allocator == Allocator::Generational {
+                undo.push((1, String::from("}")));
+            }
+
+            undo.push((0, String::from("}")));
+        }
+    }
+
+    let redo = vec![
+        (0, format!("{};", world.get_kill_call(arena, "world"))),
+    ];
+
+    body.push((0, format!("{};", world.get_kill_call(arena, "self"))));
+    body.extend(record(undo, redo));
+
+    let f = Function::new(&format!("kill_{}", field))
+        .with_parameters(&format!("&mut self, id: {}", arena.get_id_type()));
+
+    add_lines(f, with_valid_ids("self", &[(arena, "id", "valid")], body))
+}
+
+/// A journaled write to one column, keeping its index up to date. Writing a value of a unique component
+/// that another entity already holds returns an error instead.
+fn get_set(arena: &Arena, component: &ComponentType, indexed: bool) -> Function {
+    let field = arena.get_state_field().name;
+    let column = format!("{}.{}", field, component.name);
+    let unique = indexed && component.is_unique();
+
+    let write = |world: &str, value: &str| -> Vec<Line> {
+        let mut lines = if indexed { get_index_lines(world, &field, component, value) } else { vec![] };
+        lines.push((0, format!("{}.state.{}.insert(valid, {}.clone());", world, column, value)));
+        lines
+    };
+
+    let undo = with_valid_ids("world", &[(arena, "id", "valid")], write("world", "before"));
+    let redo = with_valid_ids("world", &[(arena, "id", "valid")], write("world", "value"));
+
+    let mut body = vec![
+        (0, format!("let before = self.state.{}[valid].clone();", column)),
+    ];
+
+    if unique {
+        let index = component.get_index_name();
+        let (indent, key) = match component.storage {
+            Storage::LinearOption => {
+                body.push((0, String::from("if let Some(key) = &value {")));
+                (1, "key")
+            },
+            _ => (0, "&value"),
+        };
+
+        body.push((indent, format!("if let Some(existing) = self.state.{}.{}.conflict({}, &valid.id()) {{", field, index, key)));
+        body.push((indent + 1, format!("return Err(DuplicateKey::new(\"{}\", existing));", component.name)));
+        body.push((indent, String::from("}")));
+
+        if indent > 0 {
+            body.push((0, String::from("}")));
+        }
+    }
+
+    body.extend(write("self", "value"));
+    body.extend(record(undo, redo));
+
+    let mut lines = with_valid_ids("self", &[(arena, "id", "valid")], body);
+
+    let mut f = Function::new(&format!("set_{}_{}", field, component.name))
+        .with_parameters(&format!(
+            "&mut self, id: {}, value: {}",
+            arena.get_id_type(),
+            component.storage.get_row_data_type(&component.data_type),
+        ));
+
+    if unique {
+        f = f.with_return(format!("Result<(), DuplicateKey<{}>>", arena.get_id_type()));
+        lines.push((0, String::from("Ok(())")));
+    }
+
+    add_lines(f, lines)
+}
+
+/// Points the index of the component at the new value, or removes the entity from it if an optional value is `None`.
+fn get_index_lines(world: &str, field: &SnakeCase, component: &ComponentType, value: &str) -> Vec<Line> {
+    let index = format!("{}.state.{}.{}", world, field, component.get_index_name());
+
+    match component.storage {
+        Storage::LinearOption => vec![
+            (0, format!("match &{} {{", value)),
+            (1, format!("Some(key) => {}.insert(valid.id(), key.clone()),", index)),
+            (1, format!("None => {}.remove(&valid.id()),", index)),
+            (0, String::from("}")),
+        ],
+        _ => vec![
+            (0, format!("{}.insert(valid.id(), {}.clone());", index, value)),
+        ],
+    }
+}
+
+/// A journaled call to the generated `Link` or `Unlink` implementation, restoring the link columns on undo.
+fn get_link(world: &World, link: &links::Link, link_trait: &str) -> Function {
+    let from = world.get_arena(&link.from);
+    let to = world.get_arena(&link.to);
+    let from_field = from.get_state_field().name;
+    let to_field = to.get_state_field().name;
+    let ids = [(from, "a", "valid_a"), (to, "b", "valid_b")];
+
+    let mut columns = vec![(format!("{}.{}", from_field, to_field), "valid_a", "before_a", find_backlink(world, from, to), "a")];
+    if to.references(from) {
+        columns.push((format!("{}.{}", to_field, from_field), "valid_b", "before_b", find_backlink(world, to, from), "b"));
+    }
+
+    // only Link takes edge data, which is cloned so that redo can link again
+    let (edge, parameters) = match (link_trait, link.get_edge_type(world)) {
+        ("Link", Some(edge_type)) => (String::from(", edge.clone()"), format!(", edge: {}", edge_type)),
+        ("Link", None) => (String::from(", ()"), String::new()),
+        _ => (String::new(), String::new()),
+    };
+
+    let call = |w: &str| format!(
+        "{}::<{}, {}>::{}(&mut {}.state, valid_a, valid_b{});",
+        link_trait,
+        from.name,
+        to.name,
+        link_trait.to_lowercase(),
+        w,
+        edge,
+    );
+
+    let mut restore = vec![];
+
+    for (column, valid, before, backlink, id) in columns.iter() {
+        if let Some(backlink) = backlink {
+            restore.extend(backlink.get_untrack_lines(world, "world.state", id, valid));
+        }
+
+        restore.push((0, format!("world.state.{}.insert({}, {}.clone());", column, valid, before)));
+
+        if let Some(backlink) = backlink {
+            restore.extend(backlink.get_track_lines(world, "world.state", id, valid));
+        }
+    }
+
+    let undo = with_valid_ids("world", &ids, restore);
+    let redo = with_valid_ids("world", &ids, vec![(0, call("world"))]);
+
+    let mut body: Vec<Line> = columns.iter()
+        .map(|(column, valid, before, _, _)| (0, format!("let {} = self.state.{}[{}].clone();", before, column, valid)))
+        .collect();
+    body.push((0, call("self")));
+    body.extend(record(undo, redo));
+
+    let f = Function::new(&format!("{}_{}_{}", link_trait.to_lowercase(), from_field, to_field))
+        .with_parameters(&format!("&mut self, a: {}, b: {}{}", from.get_id_type(), to.get_id_type(), parameters));
+
+    add_lines(f, with_valid_ids("self", &ids, body))
+}
+
+/// A journaled `relate_{name}` or `unrelate_{name}`, recorded only if the relation changed.
+fn get_relate(world: &World, relation: &Relation, function: &str, method: &str, inverse: &str) -> Function {
+    let undo = vec![(0, format!("world.state.{}.{}(a, b);", relation.name, inverse))];
+    let redo = vec![(0, format!("world.state.{}.{}(a, b);", relation.name, method))];
+
+    relation.get_change(world, function, method, record(undo, redo))
+}
+
+/// Captures the parent and children of `valid` before an operation that detaches it from the hierarchy.
+fn get_hierarchy_capture(arena: &Arena) -> Vec<Line> {
+    let field = arena.get_state_field().name;
+
+    vec![
+        (0, format!("let parent = self.state.{}.hierarchy.parent(valid.id());", field)),
+        (0, format!("let children = self.state.{}.hierarchy.children(valid.id()).to_vec();", field)),
+    ]
+}
+
+/// Reattaches `valid` to the parent and children captured by `get_hierarchy_capture`.
+fn get_hierarchy_restore(arena: &Arena) -> Vec<Line> {
+    let field = arena.get_state_field().name;
+
+    vec![
+        (0, String::from("if let Some(parent) = parent {")),
+        (1, format!("world.state.{}.hierarchy.set_parent(valid.id(), parent){};", field, EXPECT_HIERARCHY)),
+        (0, String::from("}")),
+        (0, String::from("for child in children.iter() {")),
+        (1, format!("world.state.{}.hierarchy.set_parent(*child, valid.id()){};", field, EXPECT_HIERARCHY)),
+        (0, String::from("}")),
+    ]
+}
+
+/// Pushes an event from a closure that bypasses `create` and `kill`, so that systems see the entity come and go.
+fn get_push_event(arena: &Arena, event: &str) -> Option<Line> {
+    if !arena.events {
+        return None;
+    }
+
+    Some((0, format!("world.state.{}.events.push(Event::{}(id));", arena.get_state_field().name, event)))
+}
+
+/// Wraps the body in a check that each generational id is still alive. Each id is bound to a reference
+/// that can index columns: fixed ids directly, and generational ids through `verify`.
+fn with_valid_ids(world: &str, ids: &[(&Arena, &str, &str)], body: Vec<Line>) -> Vec<Line> {
+    let mut lines = vec![];
+    let mut patterns = vec![];
+    let mut verifications = vec![];
+
+    for (arena, id, valid) in ids.iter() {
+        match arena.allocator {
+            Allocator::Fixed => lines.push((0, format!("let {} = &{};", valid, id))),
+            Allocator::Generational => {
+                patterns.push(format!("Some({})", valid));
+                verifications.push(format!("{}.allocators.{}.verify({})", world, arena.get_state_field().name, id));
+            },
+        }
+    }
+
+    let condition = match patterns.len() {
+        0 => None,
+        1 => Some(format!("if let {} = {} {{", patterns[0], verifications[0])),
+        _ => Some(format!("if let ({}) = ({}) {{", patterns.join(", "), verifications.join(", "))),
+    };
+
+    match condition {
+        Some(condition) => {
+            lines.push((0, condition));
+            lines.extend(body.into_iter().map(|(indent, line)| (indent + 1, line)));
+            lines.push((0, String::from("}")));
+        },
+        None => lines.extend(body),
+    }
+
+    lines
+}
+
+fn record(undo: Vec<Line>, redo: Vec<Line>) -> Vec<Line> {
+    let mut lines = vec![(0, String::from("self.journal.record("))];
+
+    for closure in vec![undo, redo] {
+        lines.push((1, String::from("move |world: &mut World| {")));
+        lines.extend(closure.into_iter().map(|(indent, line)| (indent + 2, line)));
+        lines.push((1, String::from("},")));
+    }
+
+    lines.push((0, String::from(");")));
+    lines
+}
+
+fn add_lines(mut f: Function, lines: Vec<Line>) -> Function {
+    for (indent, line) in lines {
+        f = f.add_line(CodeLine::new(indent, &line));
+    }
+    f
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::fleet;
+
+    #[derive(Debug)]
+    struct Ship;
+
+    #[test]
+    fn undoing_create_and_kill_pushes_events() {
+        let generated = fleet().impl_world().to_string();
+
+        assert!(generated.contains("world.state.ship.events.push(Event::Killed(id));"));
+        assert!(generated.contains("world.state.ship.events.push(Event::Created(id));"));
+    }
+
+    #[test]
+    fn create_redo_recreates_the_same_id() {
+        let generated = fleet().impl_world().to_string();
+
+        assert!(generated.contains("world.allocators.ship.uncreate(id);"));
+        assert!(generated.contains("let valid = world.allocators.ship.recreate(id);"));
+        assert!(generated.contains("let valid = &world.allocators.system.create_reserved(id);"));
+    }
+
+    #[test]
+    fn kill_undo_revives_and_restores_the_entity() {
+        let world = fleet();
+        let generated = world.impl_world().to_string();
+        let ship = world.get_arena(&"Ship".parse().unwrap());
+        let entity = impl_entity(&world, ship).to_string();
+
+        assert!(generated.contains("let entity = self.state.ship.get_entity(valid);"));
+        assert!(generated.contains("let valid = world.allocators.ship.revive(id);"));
+        assert!(generated.contains("world.state.ship.set_entity(valid, entity.clone());"));
+        assert!(generated.contains("world.state.ship.hierarchy.set_parent(valid.id(), parent)"));
+        assert!(entity.contains("speed_history: self.speed.iter_history(id).cloned().collect(),"));
+        assert!(entity.contains("self.speed.set_history(id, entity.speed_history);"));
+    }
+
+    #[test]
+    fn insert_undo_restores_the_entity_and_hierarchy() {
+        let generated = fleet().impl_world().to_string();
+
+        assert!(generated.contains("let before = self.state.ship.get_entity(valid);"));
+        assert!(generated.contains("world.state.ship.set_entity(valid, before.clone());"));
+        assert!(generated.contains("let children = self.state.ship.hierarchy.children(valid.id()).to_vec();"));
+        assert!(generated.contains("world.state.ship.hierarchy.set_parent(*child, valid.id())"));
+    }
+
+    #[test]
+    fn link_undo_restores_the_column() {
+        let generated = fleet().impl_world().to_string();
+
+        assert!(generated.contains("fn link_ship_system("));
+        assert!(generated.contains("let before_a = self.state.ship.system[valid_a].clone();"));
+        assert!(generated.contains("world.state.ship.system.insert(valid_a, before_a.clone());"));
+    }
+
+    #[test]
+    fn setters_cover_default_and_indexed_components() {
+        let generated = fleet().impl_world().to_string();
+
+        assert!(generated.contains("fn set_ship_heading("));
+        assert!(generated.contains("Some(key) => self.state.ship.class_index.insert(valid.id(), key.clone()),"));
+        assert!(generated.contains("None => world.state.ship.class_index.remove(&valid.id()),"));
+        assert!(generated.contains("world.state.system.name_index.insert(valid.id(), before.clone());"));
+    }
+
+    #[test]
+    fn unique_setter_returns_duplicate_key() {
+        let generated = fleet().impl_world().to_string();
+
+        assert!(generated.contains("fn set_system_name("));
+        assert!(generated.contains("if let Some(existing) = self.state.system.name_index.conflict(&value, &valid.id()) {"));
+        assert!(generated.contains("return Err(DuplicateKey::new(\"name\", existing));"));
+    }
+
+    fn create(journal: &mut Journal<GenAllocator<Ship>>, allocator: &mut GenAllocator<Ship>) -> GenId<Ship> {
+        let id = allocator.create().id;
+        journal.record(
+            move |a: &mut GenAllocator<Ship>| a.uncreate(id),
+            move |a: &mut GenAllocator<Ship>| { a.recreate(id); },
+        );
+        id
+    }
+
+    fn kill(journal: &mut Journal<GenAllocator<Ship>>, allocator: &mut GenAllocator<Ship>, id: GenId<Ship>) {
+        allocator.kill(id);
+        journal.record(
+            move |a: &mut GenAllocator<Ship>| { a.revive(id); },
+            move |a: &mut GenAllocator<Ship>| a.kill(id),
+        );
+    }
+
+    #[test]
+    fn undone_create_is_redone_with_the_same_id() {
+        let mut journal = Journal::new();
+        let mut allocator = GenAllocator::new();
+
+        let id = create(&mut journal, &mut allocator);
+
+        assert!(journal.undo(&mut allocator));
+        assert!(!allocator.is_alive(id));
+
+        assert!(journal.redo(&mut allocator));
+        assert!(allocator.is_alive(id));
+        assert_eq!(1, allocator.stats().created);
+    }
+
+    #[test]
+    fn undone_kill_revives_the_same_id() {
+        let mut journal = Journal::new();
+        let mut allocator = GenAllocator::new();
+
+        let id = create(&mut journal, &mut allocator);
+        kill(&mut journal, &mut allocator, id);
+
+        assert!(journal.undo(&mut allocator));
+        assert!(allocator.is_alive(id));
+        assert_eq!(0, allocator.stats().killed);
+
+        assert!(journal.redo(&mut allocator));
+        assert!(!allocator.is_alive(id));
+    }
+}
@@ -6,6 +6,28 @@ mod traits;
 mod ids;
 mod allocators;
 mod compound_entity;
+mod indexes;
+mod hierarchy;
+mod systems;
+mod commands;
+mod command_queue;
+mod duplicate;
+mod filters;
+mod snapshots;
+mod journal;
+mod journaled;
+mod events;
+mod relations;
+mod relation_table;
+mod backlinks;
+mod integrity;
+mod integrity_check;
+mod plugins;
+mod templates;
+mod row_builders;
+mod splits;
+#[cfg(test)]
+mod fixtures;
 
 pub use world::*;
 pub use traits::*;
@@ -16,9 +38,37 @@ pub use ids::*;
 pub use allocators::*; // TODO setup prelude
 pub use prelude::*;
 pub use compound_entity::*;
+pub use indexes::*;
+pub use hierarchy::*;
+pub use systems::*;
+pub use commands::*;
+pub use command_queue::*;
+pub use duplicate::*;
+pub use filters::*;
+pub use snapshots::*;
+pub use journal::*;
+pub use journaled::*;
+pub use events::*;
+pub use relations::*;
+pub use relation_table::*;
+pub use backlinks::*;
+pub use integrity::*;
+pub use integrity_check::*;
+pub use plugins::*;
+pub use templates::*;
+pub use row_builders::*;
+pub use splits::*;
 
 pub mod prelude {
-    pub use crate::allocators::{FixedAllocator, GenAllocator, Component};
-    pub use crate::ids::{Id, GenId, Valid};
+    pub use crate::allocators::{FixedAllocator, GenAllocator, Component, Sparse, Tag, History, Presence, FixedReservations, GenReservations, Remap, AllocatorStats, ArenaStats};
+    pub use crate::ids::{Id, GenId, Valid, ValidId, ParseIdError};
+    pub use crate::indexes::{UniqueIndex, MultiIndex, DuplicateKey};
+    pub use crate::hierarchy::{Hierarchy, HierarchyError};
+    pub use crate::command_queue::{CommandQueue, CommandError};
+    pub use crate::snapshots::{Snapshots, Shared};
+    pub use crate::journal::Journal;
+    pub use crate::events::{Event, EventQueue};
+    pub use crate::relation_table::RelationTable;
+    pub use crate::integrity::IntegrityError;
     pub use crate::traits::*;
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
-use code_gen::{CamelCase, TraitImplementation, Generics, TypeName, TraitFunction, CodeLine};
-use crate::{World, get_link_trait, Arena};
+use code_gen::{CamelCase, TraitImplementation, Generics, TypeName, TraitFunction, CodeLine, Type, Function, SnakeCase};
+use crate::{World, get_link_trait, get_unlink_trait, Arena, find_backlink};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum LinkType {
@@ -15,60 +15,195 @@ pub struct Link {
 }
 
 impl Link {
-    // function only returns some under certain conditions to prevent duplication
-    // if from owns to, return the implementation
-    // if to owns from, return nothing
-    // if they reference each other, return some if from > to, and none if to > from
     pub fn get_implementation(&self, world: &World, link_type: &LinkType) -> Option<TraitImplementation> {
+        if self.is_implemented(world) {
+            Some(self.get_implementation_unchecked(world, link_type))
+        } else {
+            None
+        }
+    }
+
+    pub fn get_unlink_implementation(&self, world: &World, link_type: &LinkType) -> Option<TraitImplementation> {
+        if self.is_implemented(world) && self.is_unlinkable(world, link_type) {
+            Some(self.get_unlink_implementation_unchecked(world, link_type))
+        } else {
+            None
+        }
+    }
+
+    // only true under certain conditions to prevent duplication
+    // if from owns to, return true
+    // if to owns from, return false
+    // if they reference each other, return true if from > to, and false if to > from
+    pub fn is_implemented(&self, world: &World) -> bool {
         let from = world.get_arena(&self.from);
         let to = world.get_arena(&self.to);
 
         if from.owns(to) {
-            Some(self.get_implementation_unchecked(world, link_type))
+            true
+        } else if to.owns(from) {
+            false
+        } else if both_reference_each_other(from, to) {
+            from.name > to.name
         } else {
-            if to.owns(from) {
-                return None;
-            } else {
-                if both_reference_each_other(from, to) {
-                    if from.name > to.name {
-                        Some(self.get_implementation_unchecked(world, link_type))
-                    } else {
-                        None
-                    }
-                } else {
-                    Some(self.get_implementation_unchecked(world, link_type))
-                }
+            true
+        }
+    }
+
+    // a link can only be undone if at least one side is optional
+    pub fn is_unlinkable(&self, world: &World, link_type: &LinkType) -> bool {
+        let from = world.get_arena(&self.from);
+        let to = world.get_arena(&self.to);
+
+        *link_type == LinkType::Optional
+            || to.references.get(&from.name) == Some(&LinkType::Optional)
+    }
+
+    /// The edge data stored with the link, which both sides of a two-way link must agree on.
+    pub fn get_edge_type(&self, world: &World) -> Option<Type> {
+        world.get_arena(&self.from).get_edge_type(&self.to).cloned()
+    }
+
+    /// The value passed as `edge` when calling `Link::link`: the given expression, or `()` for links without edge data.
+    pub fn get_edge_argument(&self, world: &World, edge: &str) -> String {
+        match self.get_edge_type(world) {
+            Some(_) => edge.to_string(),
+            None => String::from("()"),
+        }
+    }
+
+    /// `set_{from}_{to}_edge` on the State, which replaces the edge data on both sides of a two-way link
+    /// between `a` and `b`, so that they keep agreeing. Each side is only written if it links to the other.
+    pub fn get_set_edge(&self, world: &World) -> Option<Function> {
+        let from = world.get_arena(&self.from);
+        let to = world.get_arena(&self.to);
+        let edge = self.get_edge_type(world)?;
+        let reverse = to.ownership.get(&from.name).or_else(|| to.references.get(&from.name))?;
+
+        if !self.is_implemented(world) {
+            return None;
+        }
+
+        let from_field = from.get_state_field().name;
+        let to_field = to.get_state_field().name;
+        let forward = from.ownership.get(&to.name).or_else(|| from.references.get(&to.name))?;
+
+        let mut f = Function::new(&format!("set_{}_{}_edge", from_field, to_field))
+            .with_parameters(&format!(
+                "&mut self, a: &{}, b: &{}, edge: {}",
+                from.get_valid_id_type(),
+                to.get_valid_id_type(),
+                edge,
+            ));
+
+        let sides = [
+            (&from_field, &to_field, "a", "b", forward, "edge.clone()"),
+            (&to_field, &from_field, "b", "a", reverse, "edge"),
+        ];
+
+        for (field, column, id, other, link_type, value) in sides.iter() {
+            let lines = set_edge_lines(field, column, id, other, **link_type, value);
+            for (indent, line) in lines {
+                f = f.add_line(CodeLine::new(indent, &line));
             }
         }
+
+        Some(f)
     }
 
     fn get_implementation_unchecked(&self, world: &World, link_type: &LinkType) -> TraitImplementation {
         let from = world.get_arena(&self.from);
         let to = world.get_arena(&self.to);
-        let link_trait = get_link_trait();
+        let edge_type = self.get_edge_type(world);
+        let reverse = to.references.get(&from.name);
+
+        let parameters = match edge_type {
+            Some(_) => "&mut self, a: &Self::IdA, b: &Self::IdB, edge: Self::Edge",
+            None => "&mut self, a: &Self::IdA, b: &Self::IdB, _edge: Self::Edge",
+        };
+
+        let link_value = |id: &str, edge: &str| match edge_type {
+            Some(_) => format!("({}, {})", id, edge),
+            None => id.to_string(),
+        };
+
+        let forward_edge = if reverse.is_some() { "edge.clone()" } else { "edge" };
+        let forward = link_value("b.id()", forward_edge);
+
+        let forward_backlink = find_backlink(world, from, to);
+        let backward_backlink = find_backlink(world, to, from).filter(|_| reverse.is_some());
 
         let mut f = TraitFunction::new("link")
-            .with_parameters(&link_trait.functions[0].parameters)
+            .with_parameters(parameters);
+
+        // replacing the entity an optional link points at unlinks it; a one-way link is only seen from `a`,
+        // so the replaced entity is told too
+        if *link_type == LinkType::Optional {
+            let [from_event, to_event] = get_link_events(from, to, "Unlinked", "a.id()", "replaced");
+            let events = from_event.into_iter().chain(to_event.filter(|_| reverse.is_none()));
+            let current = get_linked_id(from, to, &format!("self.{}.{}[a]", from.get_state_field().name, to.get_state_field().name));
+
+            for (indent, line) in get_replace_lines(&current, "b.id()", events) {
+                f = f.add_line(CodeLine::new(indent, &line));
+            }
+        }
+
+        for backlink in forward_backlink.iter() {
+            for (indent, line) in backlink.get_untrack_lines(world, "self", "a.id()", "a") {
+                f = f.add_line(CodeLine::new(indent, &line));
+            }
+        }
+
+        f = f
             .add_line(CodeLine::new(0, &format!(
                 "self.{}.{}.insert(a, {});",
                 from.get_state_field().name,
                 to.get_state_field().name,
                 match link_type {
-                    LinkType::Required => "b.id()",
-                    LinkType::Optional => "Some(b.id())",
+                    LinkType::Required => forward,
+                    LinkType::Optional => format!("Some({})", forward),
                 }
             )));
 
-        if let Some(link_type) = to.references.get(&from.name) {
+        for backlink in forward_backlink.iter() {
+            f = f.add_line(CodeLine::new(0, &format!("self.{}.insert(a.id(), b.id());", backlink.get_name())));
+        }
+
+        if let Some(link_type) = reverse {
+            let backward = link_value("a.id()", "edge");
+
+            if *link_type == LinkType::Optional {
+                let [_, to_event] = get_link_events(from, to, "Unlinked", "replaced", "b.id()");
+                let current = get_linked_id(to, from, &format!("self.{}.{}[b]", to.get_state_field().name, from.get_state_field().name));
+
+                for (indent, line) in get_replace_lines(&current, "a.id()", to_event) {
+                    f = f.add_line(CodeLine::new(indent, &line));
+                }
+            }
+
+            for backlink in backward_backlink.iter() {
+                for (indent, line) in backlink.get_untrack_lines(world, "self", "b.id()", "b") {
+                    f = f.add_line(CodeLine::new(indent, &line));
+                }
+            }
+
             f = f.add_line(CodeLine::new(0, &format!(
                 "self.{}.{}.insert(b, {});",
                 to.get_state_field().name,
                 from.get_state_field().name,
                 match link_type {
-                    LinkType::Required => "a.id()",
-                    LinkType::Optional => "Some(a.id())",
+                    LinkType::Required => backward,
+                    LinkType::Optional => format!("Some({})", backward),
                 }
             )));
+
+            for backlink in backward_backlink.iter() {
+                f = f.add_line(CodeLine::new(0, &format!("self.{}.insert(b.id(), a.id());", backlink.get_name())));
+            }
+        }
+
+        for line in get_link_events(from, to, "Linked", "a.id()", "b.id()").iter().flatten() {
+            f = f.add_line(CodeLine::new(0, line));
         }
 
         let i = get_link_trait()
@@ -76,9 +211,165 @@ impl Link {
             .with_generics(Generics::two(from.name.as_str(), to.name.as_str()))
             .add_associated_type(TypeName::new("IdA"), from.get_valid_id_type())
             .add_associated_type(TypeName::new("IdB"), to.get_valid_id_type())
+            .add_associated_type(TypeName::new("Edge"), edge_type.unwrap_or_else(|| Type::new("()")))
             .add_function(f);
         i
     }
+
+    fn get_unlink_implementation_unchecked(&self, world: &World, link_type: &LinkType) -> TraitImplementation {
+        let from = world.get_arena(&self.from);
+        let to = world.get_arena(&self.to);
+        let unlink_trait = get_unlink_trait();
+
+        let from_field = from.get_state_field().name;
+        let to_field = to.get_state_field().name;
+
+        let mut f = TraitFunction::new("unlink")
+            .with_parameters(&unlink_trait.functions[0].parameters);
+
+        let from_clears = *link_type == LinkType::Optional;
+        let to_clears = to.references.get(&from.name) == Some(&LinkType::Optional);
+        let [from_event, to_event] = get_link_events(from, to, "Unlinked", "a.id()", "b.id()");
+
+        // each side's event is pushed where its own link is cleared, or where the other side's is if it has none
+        let linked_id = |arena: &Arena, other: &Arena| match arena.get_edge_type(&other.name) {
+            Some(_) => ".as_ref().map(|(id, _)| *id)",
+            None => "",
+        };
+
+        if from_clears {
+            f = f
+                .add_line(CodeLine::new(0, &format!("if self.{}.{}[a]{} == Some(b.id()) {{", from_field, to_field, linked_id(from, to))))
+                .add_line(CodeLine::new(1, &format!("self.{}.{}.insert(a, None);", from_field, to_field)));
+
+            if let Some(backlink) = find_backlink(world, from, to) {
+                f = f.add_line(CodeLine::new(1, &format!("self.{}.remove(a.id(), b.id());", backlink.get_name())));
+            }
+
+            for line in from_event.iter().chain(to_event.iter().filter(|_| !to_clears)) {
+                f = f.add_line(CodeLine::new(1, line));
+            }
+
+            f = f.add_line(CodeLine::new(0, "}"));
+        }
+
+        if to_clears {
+            f = f
+                .add_line(CodeLine::new(0, &format!("if self.{}.{}[b]{} == Some(a.id()) {{", to_field, from_field, linked_id(to, from))))
+                .add_line(CodeLine::new(1, &format!("self.{}.{}.insert(b, None);", to_field, from_field)));
+
+            if let Some(backlink) = find_backlink(world, to, from) {
+                f = f.add_line(CodeLine::new(1, &format!("self.{}.remove(b.id(), a.id());", backlink.get_name())));
+            }
+
+            for line in to_event.iter().chain(from_event.iter().filter(|_| !from_clears)) {
+                f = f.add_line(CodeLine::new(1, line));
+            }
+
+            f = f.add_line(CodeLine::new(0, "}"));
+        }
+
+        unlink_trait
+            .impl_for(&world.get_state())
+            .with_generics(Generics::two(from.name.as_str(), to.name.as_str()))
+            .add_associated_type(TypeName::new("IdA"), from.get_valid_id_type())
+            .add_associated_type(TypeName::new("IdB"), to.get_valid_id_type())
+            .add_function(f)
+    }
+}
+
+/// Replaces the edge data in `field.column[id]` if it links to `other`.
+fn set_edge_lines(field: &SnakeCase, column: &SnakeCase, id: &str, other: &str, link_type: LinkType, value: &str) -> Vec<(usize, String)> {
+    match link_type {
+        LinkType::Required => vec![
+            (0, format!("let link = &mut self.{}.{}[{}];", field, column, id)),
+            (0, format!("if link.0 == {}.id() {{", other)),
+            (1, format!("link.1 = {};", value)),
+            (0, String::from("}")),
+        ],
+        LinkType::Optional => vec![
+            (0, format!("if let Some((linked, current)) = &mut self.{}.{}[{}] {{", field, column, id)),
+            (1, format!("if *linked == {}.id() {{", other)),
+            (2, format!("*current = {};", value)),
+            (1, String::from("}")),
+            (0, String::from("}")),
+        ],
+    }
+}
+
+/// The lines that push a link event to each side of the link that has events, `from` first.
+/// `a` and `b` are the ids of the entities on the `from` and `to` side.
+fn get_link_events(from: &Arena, to: &Arena, event: &str, a: &str, b: &str) -> [Option<String>; 2] {
+    let push = |arena: &Arena, id: &str, other: &Arena, other_id: &str| {
+        if arena.events {
+            Some(format!(
+                "self.{}.events.push(Event::{}({}, {}::{}({})));",
+                arena.get_state_field().name,
+                event,
+                id,
+                get_link_enum_name(arena),
+                other.name,
+                other_id,
+            ))
+        } else {
+            None
+        }
+    };
+
+    [push(from, a, to, b), push(to, b, from, a)]
+}
+
+/// Converts a value of the link column `arena.other` into the `Option` of the linked id.
+fn get_linked_id(arena: &Arena, other: &Arena, value: &str) -> String {
+    match arena.get_edge_type(&other.name) {
+        Some(_) => format!("{}.as_ref().map(|(id, _)| *id)", value),
+        None => value.to_string(),
+    }
+}
+
+/// The lines that push the events when a link column that held `current` is pointed at `linked` instead.
+fn get_replace_lines(current: &str, linked: &str, events: impl IntoIterator<Item=String>) -> Vec<(usize, String)> {
+    let events: Vec<_> = events.into_iter().collect();
+
+    if events.is_empty() {
+        return vec![];
+    }
+
+    let mut lines = vec![
+        (0, format!("if let Some(replaced) = {} {{", current)),
+        (1, format!("if replaced != {} {{", linked)),
+    ];
+
+    lines.extend(events.into_iter().map(|event| (2, event)));
+    lines.push((1, String::from("}")));
+    lines.push((0, String::from("}")));
+    lines
+}
+
+pub fn get_link_enum_name(arena: &Arena) -> String {
+    format!("{}Link", arena.name)
+}
+
+/// The arenas linked with the arena, through a link on either side, in name order.
+pub fn get_linked_arenas<'a>(world: &'a World, arena: &Arena) -> Vec<&'a Arena> {
+    let mut linked: Vec<&Arena> = world.arenas.iter()
+        .filter(|other| arena.owns(other) || arena.references(other) || other.owns(arena) || other.references(arena))
+        .collect();
+
+    linked.sort_by_key(|other| other.name.to_string());
+    linked
+}
+
+/// `{Arena}Link`, the entity on the other side of a link event, with a variant for each linked arena.
+pub fn get_link_enum(world: &World, arena: &Arena) -> String {
+    let mut code = String::from("#[derive(Debug, Copy, Clone, Eq, PartialEq)]\n");
+    code += &format!("pub enum {} {{\n", get_link_enum_name(arena));
+
+    for other in get_linked_arenas(world, arena) {
+        code += &format!("    {}({}),\n", other.name, other.get_id_type());
+    }
+
+    code + "}\n"
 }
 
 fn both_reference_each_other(a: &Arena, b: &Arena) -> bool {
@@ -128,4 +419,26 @@ mod test {
         assert!(!a_b.overlaps(&b_c));
         assert!(!a_b.overlaps(&c_b));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn link_events_carry_the_other_side_and_replacements_unlink() {
+        let world = crate::fixtures::fleet();
+        let ship = world.get_arena(&CamelCase::from_str("Ship").unwrap());
+
+        let links = world.get_link_implementations()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert!(links.contains("self.ship.events.push(Event::Linked(a.id(), ShipLink::System(b.id())));"));
+        assert!(links.contains("if let Some(replaced) = self.ship.system[a] {"));
+        assert!(links.contains("self.ship.events.push(Event::Unlinked(a.id(), ShipLink::System(replaced)));"));
+
+        let link_enum = get_link_enum(&world, ship);
+
+        assert!(link_enum.contains("pub enum ShipLink {"));
+        assert!(link_enum.contains("    Crew(GenId<Crew>),"));
+        assert!(link_enum.contains("    System(Id<System>),"));
+    }
+}
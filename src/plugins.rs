@@ -0,0 +1,117 @@
+use crate::*;
+use code_gen::{CamelCase, Type};
+
+/// A reusable part of a World, such as the physics or economy of a game. Its arenas can link to arenas
+/// of other plugins by name; those links are resolved when the World is generated.
+#[derive(Debug, Clone)]
+pub struct WorldPlugin {
+    pub name: String,
+    pub uses: Vec<String>,
+    pub arenas: Vec<Arena>,
+    pub components: Vec<StaticComponent>,
+    pub systems: Vec<System>,
+    pub links: Vec<PluginLink>,
+}
+
+/// A link declared by a plugin, applied to the `from` arena once it has been added to the World.
+#[derive(Debug, Clone)]
+pub struct PluginLink {
+    pub from: CamelCase,
+    pub to: CamelCase,
+    pub link_type: LinkType,
+    pub ownership: bool,
+    pub edge: Option<Type>,
+}
+
+impl WorldPlugin {
+    pub fn new(name: &str) -> Self {
+        WorldPlugin {
+            name: name.to_string(),
+            uses: vec![],
+            arenas: vec![],
+            components: vec![],
+            systems: vec![],
+            links: vec![],
+        }
+    }
+
+    pub fn add_use(mut self, use_ref: &str) -> Self {
+        self.uses.push(use_ref.to_string());
+        self
+    }
+
+    pub fn add_arena(mut self, arena: Arena) -> Self {
+        self.arenas.push(arena);
+        self
+    }
+
+    pub fn add_static_component(mut self, component: StaticComponent) -> Self {
+        self.components.push(component);
+        self
+    }
+
+    pub fn add_system(mut self, system: System) -> Self {
+        self.systems.push(system);
+        self
+    }
+
+    /// Links `from` to `to` by name, where either arena may belong to another plugin.
+    pub fn add_reference(self, from: &str, to: &str, link_type: LinkType) -> Self {
+        self.add_link(from, to, link_type, false, None)
+    }
+
+    pub fn add_ownership(self, owner: &str, owned: &str, link_type: LinkType) -> Self {
+        self.add_link(owner, owned, link_type, true, None)
+    }
+
+    pub fn add_reference_with_edge(self, from: &str, to: &str, link_type: LinkType, edge: &str) -> Self {
+        self.add_link(from, to, link_type, false, Some(Type::new(edge)))
+    }
+
+    pub fn add_ownership_with_edge(self, owner: &str, owned: &str, link_type: LinkType, edge: &str) -> Self {
+        self.add_link(owner, owned, link_type, true, Some(Type::new(edge)))
+    }
+
+    fn add_link(mut self, from: &str, to: &str, link_type: LinkType, ownership: bool, edge: Option<Type>) -> Self {
+        self.links.push(PluginLink {
+            from: from.parse().unwrap(),
+            to: to.parse().unwrap(),
+            link_type,
+            ownership,
+            edge,
+        });
+        self
+    }
+}
+
+impl PluginLink {
+    /// Adds the link to its `from` arena. If the arena already links to `to` in a different way, the arena is left
+    /// as it is and the conflict is returned, to be reported when the World is validated.
+    pub fn apply(&self, arena: &mut Arena) -> Result<(), String> {
+        let links = if self.ownership { &arena.ownership } else { &arena.references };
+
+        if let Some(existing) = links.get(&self.to) {
+            if *existing != self.link_type {
+                return Err(format!("Conflicting links from {} to {}", self.from, self.to));
+            }
+        }
+
+        if let (Some(edge), Some(existing)) = (&self.edge, arena.edges.get(&self.to)) {
+            if existing.to_string() != edge.to_string() {
+                return Err(format!("Conflicting edge data from {} to {}", self.from, self.to));
+            }
+        }
+
+        if self.ownership {
+            arena.ownership.insert(self.to.clone(), self.link_type);
+        } else {
+            arena.references.insert(self.to.clone(), self.link_type);
+        }
+
+        if let Some(edge) = &self.edge {
+            arena.edges.insert(self.to.clone(), edge.clone());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,264 @@
+use crate::allocators::Remap;
+use crate::ids::{ArenaId, GenId};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A many-to-many relation between the entities of two arenas, with lookups in both directions.
+/// Each pair is stored at most once, and the related ids are kept in insertion order.
+#[derive(Debug, Clone)]
+pub struct RelationTable<A, B> {
+    targets: HashMap<A, Vec<B>>,
+    sources: HashMap<B, Vec<A>>,
+    len: usize,
+}
+
+impl<A, B> Default for RelationTable<A, B> {
+    fn default() -> Self {
+        Self {
+            targets: HashMap::new(),
+            sources: HashMap::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<A: ArenaId + Hash, B: ArenaId + Hash> RelationTable<A, B> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns false if the pair was already related.
+    pub fn insert(&mut self, a: A, b: B) -> bool {
+        if self.contains(a, b) {
+            return false;
+        }
+
+        self.targets.entry(a).or_default().push(b);
+        self.sources.entry(b).or_default().push(a);
+        self.len += 1;
+        true
+    }
+
+    /// Returns false if the pair was not related.
+    pub fn remove(&mut self, a: A, b: B) -> bool {
+        if !remove_from(&mut self.targets, a, b) {
+            return false;
+        }
+
+        remove_from(&mut self.sources, b, a);
+        self.len -= 1;
+        true
+    }
+
+    pub fn contains(&self, a: A, b: B) -> bool {
+        self.targets(a).contains(&b)
+    }
+
+    /// The entities that `a` is related to.
+    pub fn targets(&self, a: A) -> &[B] {
+        self.targets.get(&a).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The entities that are related to `b`.
+    pub fn sources(&self, b: B) -> &[A] {
+        self.sources.get(&b).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Removes every pair with `a` as the source, returning the targets it was related to.
+    pub fn remove_source(&mut self, a: A) -> Vec<B> {
+        let targets = self.targets.remove(&a).unwrap_or_default();
+
+        for b in targets.iter() {
+            remove_from(&mut self.sources, *b, a);
+        }
+
+        self.len -= targets.len();
+        targets
+    }
+
+    /// Removes every pair with `b` as the target, returning the sources it was related to.
+    pub fn remove_target(&mut self, b: B) -> Vec<A> {
+        let sources = self.sources.remove(&b).unwrap_or_default();
+
+        for a in sources.iter() {
+            remove_from(&mut self.targets, *a, b);
+        }
+
+        self.len -= sources.len();
+        sources
+    }
+
+    /// Every related pair, grouped by source.
+    pub fn iter(&self) -> impl Iterator<Item=(A, B)> + '_ {
+        self.targets.iter()
+            .flat_map(|(a, targets)| targets.iter().map(move |b| (*a, *b)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.targets.clear();
+        self.sources.clear();
+        self.len = 0;
+    }
+}
+
+impl<TA, B: ArenaId + Hash> RelationTable<GenId<TA>, B> {
+    /// Updates the sources to a compacted arena. Pairs whose source was already dead are dropped.
+    pub fn remap_sources(&mut self, remap: &Remap<TA>) {
+        let targets = std::mem::take(&mut self.targets);
+
+        self.targets = targets.into_iter()
+            .filter_map(|(a, targets)| remap.get(a).map(|a| (a, targets)))
+            .collect();
+
+        for sources in self.sources.values_mut() {
+            *sources = sources.iter().filter_map(|a| remap.get(*a)).collect();
+        }
+
+        self.sources.retain(|_, sources| !sources.is_empty());
+        self.len = self.targets.values().map(Vec::len).sum();
+    }
+}
+
+impl<A: ArenaId + Hash, TB> RelationTable<A, GenId<TB>> {
+    /// Updates the targets to a compacted arena. Pairs whose target was already dead are dropped.
+    pub fn remap_targets(&mut self, remap: &Remap<TB>) {
+        let sources = std::mem::take(&mut self.sources);
+
+        self.sources = sources.into_iter()
+            .filter_map(|(b, sources)| remap.get(b).map(|b| (b, sources)))
+            .collect();
+
+        for targets in self.targets.values_mut() {
+            *targets = targets.iter().filter_map(|b| remap.get(*b)).collect();
+        }
+
+        self.targets.retain(|_, targets| !targets.is_empty());
+        self.len = self.sources.values().map(Vec::len).sum();
+    }
+}
+
+/// Removes `value` from the list under `key`, dropping the list once it is empty.
+fn remove_from<K: Hash + Eq, V: Eq>(map: &mut HashMap<K, Vec<V>>, key: K, value: V) -> bool {
+    let list = match map.get_mut(&key) {
+        Some(list) => list,
+        None => return false,
+    };
+
+    let position = match list.iter().position(|v| *v == value) {
+        Some(position) => position,
+        None => return false,
+    };
+
+    list.remove(position);
+
+    if list.is_empty() {
+        map.remove(&key);
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocators::{FixedAllocator, GenAllocator};
+
+    #[derive(Debug)]
+    struct Colony;
+
+    #[derive(Debug)]
+    struct Fleet;
+
+    #[test]
+    fn lookups_in_both_directions() {
+        let mut colonies = FixedAllocator::<Colony>::default();
+        let mut fleets = GenAllocator::<Fleet>::default();
+        let mut table = RelationTable::new();
+
+        let home = colonies.create();
+        let outpost = colonies.create();
+        let fleet = fleets.create().id();
+
+        assert!(table.insert(fleet, home));
+        assert!(table.insert(fleet, outpost));
+        assert!(!table.insert(fleet, home));
+
+        assert_eq!(&[home, outpost], table.targets(fleet));
+        assert_eq!(&[fleet], table.sources(outpost));
+        assert_eq!(2, table.len());
+
+        assert!(table.remove(fleet, home));
+        assert!(!table.remove(fleet, home));
+        assert!(table.sources(home).is_empty());
+        assert_eq!(1, table.len());
+    }
+
+    #[test]
+    fn remove_source_clears_both_directions() {
+        let mut colonies = FixedAllocator::<Colony>::default();
+        let mut table = RelationTable::new();
+
+        let a = colonies.create();
+        let b = colonies.create();
+        let c = colonies.create();
+
+        table.insert(a, b);
+        table.insert(a, c);
+        table.insert(b, c);
+
+        assert_eq!(vec![b, c], table.remove_source(a));
+        assert_eq!(&[b], table.sources(c));
+        assert_eq!(1, table.len());
+
+        assert_eq!(vec![b], table.remove_target(c));
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn stale_ids_are_not_related() {
+        let mut fleets = GenAllocator::<Fleet>::default();
+        let mut table = RelationTable::new();
+
+        let a = fleets.create().id();
+        let b = fleets.create().id();
+        table.insert(a, b);
+
+        table.remove_source(a);
+        fleets.kill(a);
+        let reused = fleets.create().id();
+
+        assert!(table.targets(reused).is_empty());
+        assert!(table.sources(b).is_empty());
+    }
+
+    #[test]
+    fn remap_drops_dead_sources() {
+        let mut fleets = GenAllocator::<Fleet>::default();
+        let mut table = RelationTable::new();
+
+        let dead = fleets.create().id();
+        let a = fleets.create().id();
+        let b = fleets.create().id();
+        table.insert(a, b);
+        table.insert(b, a);
+        fleets.kill(dead);
+
+        let remap = fleets.compact();
+        table.remap_sources(&remap);
+        table.remap_targets(&remap);
+
+        let a = remap.get(a).unwrap();
+        let b = remap.get(b).unwrap();
+        assert_eq!(&[b], table.targets(a));
+        assert_eq!(&[a], table.targets(b));
+        assert_eq!(2, table.len());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::*;
+use code_gen::{SnakeCase, CamelCase, Field, Type, Function, CodeLine};
+
+/// A many-to-many relation between two arenas, stored in a `RelationTable` on the State.
+/// The generated World can relate, unrelate and look up entities in both directions, and
+/// killing an entity removes it from every relation it takes part in.
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub name: SnakeCase,
+    pub from: CamelCase,
+    pub to: CamelCase,
+}
+
+impl Relation {
+    pub fn new(name: &str, from: &Arena, to: &Arena) -> Self {
+        Relation {
+            name: name.parse().unwrap(),
+            from: from.name.clone(),
+            to: to.name.clone(),
+        }
+    }
+
+    pub fn involves(&self, arena: &Arena) -> bool {
+        self.from == arena.name || self.to == arena.name
+    }
+
+    pub fn get_state_field(&self, world: &World) -> Field {
+        let from = world.get_arena(&self.from);
+        let to = world.get_arena(&self.to);
+
+        Field {
+            visibility: Default::default(),
+            name: self.name.clone(),
+            field_type: Type::new(&format!("Shared<RelationTable<{}, {}>>", from.get_id_type(), to.get_id_type())),
+        }
+    }
+
+    /// The lines that remove a killed entity of the arena from this relation.
+    pub fn get_cleanup_lines(&self, arena: &Arena) -> Vec<String> {
+        let mut lines = vec![];
+
+        if self.from == arena.name {
+            lines.push(format!("self.{}.remove_source(id);", self.name));
+        }
+
+        if self.to == arena.name {
+            lines.push(format!("self.{}.remove_target(id);", self.name));
+        }
+
+        lines
+    }
+
+    /// The lines that update this relation after the arena is compacted.
+    pub fn get_remap_lines(&self, arena: &Arena) -> Vec<String> {
+        let mut lines = vec![];
+
+        if self.from == arena.name {
+            lines.push(format!("self.state.{}.remap_sources(&remap);", self.name));
+        }
+
+        if self.to == arena.name {
+            lines.push(format!("self.state.{}.remap_targets(&remap);", self.name));
+        }
+
+        lines
+    }
+
+    /// `relate_{name}` and `unrelate_{name}`, which return whether the relation changed.
+    /// Ids of dead entities are never related.
+    pub fn get_functions(&self, world: &World) -> Vec<Function> {
+        vec![
+            self.get_change(world, "relate", "insert", vec![]),
+            self.get_change(world, "unrelate", "remove", vec![]),
+        ]
+    }
+
+    /// Builds `relate_{name}` or `unrelate_{name}`, running `on_change` after the table changes.
+    pub fn get_change(&self, world: &World, function: &str, method: &str, on_change: Vec<(usize, String)>) -> Function {
+        let mut f = Function::new(&format!("{}_{}", function, self.name))
+            .with_parameters(&self.get_parameters(world))
+            .with_return(String::from("bool"));
+
+        if function == "relate" {
+            for (indent, line) in self.get_liveness_checks(world) {
+                f = f.add_line(CodeLine::new(indent, &line));
+            }
+        }
+
+        let change = format!("self.state.{}.{}(a, b)", self.name, method);
+
+        if on_change.is_empty() {
+            return f.add_line(CodeLine::new(0, &change));
+        }
+
+        f = f
+            .add_line(CodeLine::new(0, &format!("let changed = {};", change)))
+            .add_line(CodeLine::new(0, "if changed {"));
+
+        for (indent, line) in on_change {
+            f = f.add_line(CodeLine::new(indent + 1, &line));
+        }
+
+        f
+            .add_line(CodeLine::new(0, "}"))
+            .add_line(CodeLine::new(0, "changed"))
+    }
+
+    /// `{name}_from` and `{name}_to`, the entities related to a source or a target.
+    pub fn get_lookups(&self, world: &World) -> Vec<Function> {
+        let from = world.get_arena(&self.from);
+        let to = world.get_arena(&self.to);
+
+        vec![
+            Function::new(&format!("{}_from", self.name))
+                .with_parameters(&format!("&self, a: {}", from.get_id_type()))
+                .with_return(format!("&[{}]", to.get_id_type()))
+                .add_line(CodeLine::new(0, &format!("self.state.{}.targets(a)", self.name))),
+            Function::new(&format!("{}_to", self.name))
+                .with_parameters(&format!("&self, b: {}", to.get_id_type()))
+                .with_return(format!("&[{}]", from.get_id_type()))
+                .add_line(CodeLine::new(0, &format!("self.state.{}.sources(b)", self.name))),
+        ]
+    }
+
+    fn get_parameters(&self, world: &World) -> String {
+        format!(
+            "&mut self, a: {}, b: {}",
+            world.get_arena(&self.from).get_id_type(),
+            world.get_arena(&self.to).get_id_type(),
+        )
+    }
+
+    fn get_liveness_checks(&self, world: &World) -> Vec<(usize, String)> {
+        let sides = [(world.get_arena(&self.from), "a"), (world.get_arena(&self.to), "b")];
+        let mut lines = vec![];
+
+        for (arena, id) in sides.iter() {
+            if arena.allocator == Allocator::Generational {
+                lines.push((0, format!("if !self.allocators.{}.is_alive({}) {{", arena.get_state_field().name, id)));
+                lines.push((1, String::from("return false;")));
+                lines.push((0, String::from("}")));
+            }
+        }
+
+        lines
+    }
+}
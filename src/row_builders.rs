@@ -0,0 +1,84 @@
+use crate::*;
+use code_gen::{Field, Struct, Derives, Impl, Function, CodeLine, Type};
+
+// Each data row gets a builder, so that rows can be written with only the components that matter:
+// `BodyRow::builder().parameters(parameters).name("Earth").build()`. Sparse components default to
+// `None` and tags to `false`; `build` panics if a required component was never set.
+
+pub fn get_row_builder(arena: &Arena) -> Struct {
+    let fields = arena.components.iter()
+        .map(|component| Field {
+            visibility: Default::default(),
+            name: component.name.clone(),
+            field_type: get_builder_field_type(component),
+        })
+        .collect();
+
+    Struct::new(&get_row_builder_name(arena))
+        .with_derives(Derives::with_debug_default_clone())
+        .with_fields(fields)
+}
+
+pub fn impl_data_row(arena: &Arena) -> Impl {
+    let builder = get_row_builder_name(arena);
+
+    Impl::from(&arena.get_data_row().typ)
+        .add_function(Function::new("builder")
+            .with_return(builder)
+            .add_line(CodeLine::new(0, "Default::default()")))
+}
+
+pub fn impl_row_builder(arena: &Arena) -> Impl {
+    let data_row = arena.get_data_row().typ;
+    let mut builder_impl = Impl::from(&get_row_builder(arena).typ);
+
+    for component in arena.components.iter() {
+        let assignment = match component.storage {
+            Storage::Tag => format!("self.{} = value;", component.name),
+            _ => format!("self.{} = Some(value.into());", component.name),
+        };
+
+        let parameters = match component.storage {
+            Storage::Tag => String::from("mut self, value: bool"),
+            _ => format!("mut self, value: impl Into<{}>", component.data_type),
+        };
+
+        builder_impl = builder_impl.add_function(Function::new(component.name.as_str())
+            .with_parameters(&parameters)
+            .with_return(String::from("Self"))
+            .add_line(CodeLine::new(0, &assignment))
+            .add_line(CodeLine::new(0, "self")));
+    }
+
+    let mut build = Function::new("build")
+        .with_parameters("self")
+        .with_return(data_row.to_string())
+        .add_line(CodeLine::new(0, &format!("{} {{", data_row)));
+
+    for component in arena.components.iter() {
+        let line = match component.is_optional_in_row() {
+            true => format!("{name}: self.{name},", name=component.name),
+            false => format!(
+                "{name}: self.{name}.expect(\"{row}::build: {name} is required\"),",
+                name=component.name,
+                row=get_row_builder_name(arena),
+            ),
+        };
+
+        build = build.add_line(CodeLine::new(1, &line));
+    }
+
+    builder_impl.add_function(build.add_line(CodeLine::new(0, "}")))
+}
+
+fn get_row_builder_name(arena: &Arena) -> String {
+    format!("{}RowBuilder", arena.name)
+}
+
+/// Tags are stored as the `bool` they are set from; every other component is held as an `Option` until built.
+fn get_builder_field_type(component: &ComponentType) -> Type {
+    match component.storage {
+        Storage::Tag => Type::new("bool"),
+        _ => Type::new(&format!("Option<{}>", component.data_type)),
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+/// A value that clones share until one of them writes to it.
+///
+/// The generated World keeps each allocator, arena and relation table behind one, so cloning the World
+/// only clones pointers. A mutable borrow copies the value first if it is still shared, so saving a snapshot
+/// costs nothing up front, and only the parts of the World written after the save are copied, once each.
+pub struct Shared<T>(Arc<T>);
+
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Shared(Arc::new(value))
+    }
+
+    /// True if a clone, such as a snapshot, still holds the same value, so that the next write copies it.
+    pub fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Arc::ptr_eq(&a.0, &b.0)
+    }
+}
+
+impl<T: Default> Default for Shared<T> {
+    fn default() -> Self {
+        Shared::new(T::default())
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(Arc::clone(&self.0))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone> DerefMut for Shared<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+/// A bounded ring of world snapshots keyed by tick, for rolling a simulation back.
+///
+/// Each snapshot is a clone of the world. The generated World keeps its allocators, arenas and relation
+/// tables in `Shared`, so a snapshot shares them with the world, and an arena is only copied when it is
+/// first written after a save. Arenas that do not change between saves are never copied.
+#[derive(Debug, Clone)]
+pub struct Snapshots<W> {
+    capacity: usize,
+    ring: VecDeque<(u64, W)>,
+}
+
+impl<W: Clone> Snapshots<W> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Snapshots::new: capacity must be at least 1");
+
+        Self {
+            capacity,
+            ring: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Saves a clone of the world at the tick, dropping the oldest snapshot once the ring is full.
+    /// Snapshots at or after the tick are discarded, as they belong to a timeline that was rolled back.
+    pub fn save(&mut self, tick: u64, world: &W) {
+        while let Some((last, _)) = self.ring.back() {
+            if *last < tick {
+                break;
+            }
+            self.ring.pop_back();
+        }
+
+        if self.ring.len() == self.capacity {
+            self.ring.pop_front();
+        }
+
+        self.ring.push_back((tick, world.clone()));
+    }
+
+    pub fn get(&self, tick: u64) -> Option<&W> {
+        self.ring.iter()
+            .find(|(t, _)| *t == tick)
+            .map(|(_, world)| world)
+    }
+
+    /// Overwrites the world with the snapshot taken at the tick, returning false if there is none.
+    /// The snapshot is kept, so the same tick can be restored again.
+    pub fn restore(&self, tick: u64, world: &mut W) -> bool {
+        match self.get(tick) {
+            Some(snapshot) => {
+                world.clone_from(snapshot);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// The tick of the most recent snapshot at or before the given tick.
+    pub fn latest_before(&self, tick: u64) -> Option<u64> {
+        self.ring.iter()
+            .rev()
+            .map(|(t, _)| *t)
+            .find(|t| *t <= tick)
+    }
+
+    pub fn oldest_tick(&self) -> Option<u64> {
+        self.ring.front().map(|(tick, _)| *tick)
+    }
+
+    pub fn newest_tick(&self) -> Option<u64> {
+        self.ring.back().map(|(tick, _)| *tick)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ring.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.ring.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocators::GenAllocator;
+
+    #[derive(Debug)]
+    struct Ship;
+
+    #[test]
+    fn ring_drops_oldest() {
+        let mut snapshots = Snapshots::new(2);
+
+        snapshots.save(1, &vec![1]);
+        snapshots.save(2, &vec![2]);
+        snapshots.save(3, &vec![3]);
+
+        assert_eq!(Some(2), snapshots.oldest_tick());
+        assert_eq!(Some(3), snapshots.newest_tick());
+        assert!(snapshots.get(1).is_none());
+    }
+
+    #[test]
+    fn save_discards_rolled_back_ticks() {
+        let mut snapshots = Snapshots::new(4);
+
+        snapshots.save(1, &vec![1]);
+        snapshots.save(2, &vec![2]);
+        snapshots.save(3, &vec![3]);
+        snapshots.save(2, &vec![20]);
+
+        assert_eq!(2, snapshots.len());
+        assert_eq!(Some(&vec![20]), snapshots.get(2));
+        assert_eq!(Some(2), snapshots.latest_before(5));
+    }
+
+    #[test]
+    fn shared_copies_on_the_first_write_after_a_clone() {
+        let mut world = (Shared::new(vec![1]), Shared::new(vec![2]));
+        let snapshot = world.clone();
+
+        assert!(world.0.is_shared());
+
+        world.0.push(3);
+        world.0.push(4);
+
+        assert_eq!(vec![1, 3, 4], *world.0);
+        assert_eq!(vec![1], *snapshot.0);
+        assert!(!world.0.is_shared());
+        assert!(Shared::ptr_eq(&world.1, &snapshot.1));
+    }
+
+    #[test]
+    fn restore_brings_back_dead_entities() {
+        let mut snapshots = Snapshots::new(4);
+        let mut allocator = GenAllocator::<Ship>::new();
+
+        let id = allocator.create().id;
+        snapshots.save(1, &allocator);
+
+        allocator.kill(id);
+        let reused = allocator.create().id;
+
+        assert!(snapshots.restore(1, &mut allocator));
+        assert!(allocator.is_alive(id));
+        assert!(!allocator.is_alive(reused));
+        assert!(!snapshots.restore(2, &mut allocator));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::*;
+use code_gen::{SnakeCase, CamelCase, Struct, Field, Type, Function, CodeLine};
+
+/// A named borrow of some columns of an arena: shared for the columns it reads and mutable for those it writes.
+/// Generates `{Arena}{Name}<'a>`, `Arena::split_{name}` and `State::split_{arena}_{name}`.
+#[derive(Debug, Clone)]
+pub struct ColumnSplit {
+    pub name: SnakeCase,
+    pub reads: Vec<SnakeCase>,
+    pub writes: Vec<SnakeCase>,
+}
+
+impl ColumnSplit {
+    pub fn new(name: &str, reads: &[&str], writes: &[&str]) -> Self {
+        ColumnSplit {
+            name: name.parse().unwrap(),
+            reads: reads.iter().map(|c| c.parse().unwrap()).collect(),
+            writes: writes.iter().map(|c| c.parse().unwrap()).collect(),
+        }
+    }
+
+    /// The name of the generated struct, e.g. `BodyOrbits` for the split `orbits` of `Body`.
+    fn get_struct_name(&self, arena: &Arena) -> String {
+        let name: CamelCase = self.name.clone().into();
+
+        format!("{}{}", arena.name, name)
+    }
+
+    pub fn get_type(&self, arena: &Arena) -> Type {
+        Type::new(&format!("{}<'a>", self.get_struct_name(arena)))
+    }
+
+    pub fn get_struct(&self, world: &World, arena: &Arena) -> Struct {
+        let columns = arena.get_columns(world);
+        let find = |name: &SnakeCase| columns.iter()
+            .find(|c| c.name == *name)
+            .unwrap_or_else(|| panic!("Split {} of {} uses a missing column: {}", self.name, arena.name, name));
+
+        let reads = self.reads.iter().map(|c| find(c).get_split_field(&arena.name, false));
+        let writes = self.writes.iter().map(|c| find(c).get_split_field(&arena.name, true));
+
+        Struct {
+            typ: self.get_type(arena),
+            visibility: Default::default(),
+            derives: Default::default(),
+            fields: reads.chain(writes).collect::<Vec<Field>>(),
+        }
+    }
+
+    pub fn get_function(&self, arena: &Arena) -> Function {
+        let name = self.get_struct_name(arena);
+        let mut split = Function::new(&format!("split_{}", self.name))
+            .with_parameters("&mut self")
+            .with_return(format!("{}<'_>", name))
+            .add_line(CodeLine::new(0, &format!("{} {{", name)));
+
+        for column in self.reads.iter() {
+            split = split.add_line(CodeLine::new(1, &format!("{name}: &self.{name},", name=column)));
+        }
+
+        for column in self.writes.iter() {
+            split = split.add_line(CodeLine::new(1, &format!("{name}: &mut self.{name},", name=column)));
+        }
+
+        split.add_line(CodeLine::new(0, "}"))
+    }
+
+    pub fn get_state_function(&self, arena: &Arena) -> Function {
+        let field = arena.get_state_field().name;
+
+        Function::new(&format!("split_{}_{}", field, self.name))
+            .with_parameters("&mut self")
+            .with_return(format!("{}<'_>", self.get_struct_name(arena)))
+            .add_line(CodeLine::new(0, &format!("self.{}.split_{}()", field, self.name)))
+    }
+
+    /// Every column exists and appears once, so the borrows do not overlap. Indexed columns and optional links
+    /// to generational arenas are read-only, as writing them directly would leave their index or backlinks stale.
+    pub fn is_valid(&self, world: &World, arena: &Arena) -> bool {
+        let columns = arena.get_columns(world);
+        let all = self.reads.iter().chain(self.writes.iter()).collect::<Vec<_>>();
+
+        let backlinked = get_backlinks(world)
+            .into_iter()
+            .filter(|backlink| backlink.from == arena.name)
+            .map(|backlink| SnakeCase::from(backlink.to))
+            .collect::<Vec<_>>();
+
+        let writable = |name: &SnakeCase| !backlinked.contains(name)
+            && columns.iter().all(|c| c.name != *name || c.index.is_none());
+
+        all.iter().all(|name| columns.iter().any(|c| c.name == **name))
+            && all.iter().enumerate().all(|(i, name)| !all[..i].contains(name))
+            && self.writes.iter().all(writable)
+    }
+}
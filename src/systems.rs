@@ -0,0 +1,107 @@
+use crate::Arena;
+use code_gen::{CamelCase, SnakeCase, CodeLine};
+
+/// An arena column accessed by a system.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ColumnRef {
+    pub arena: CamelCase,
+    pub column: SnakeCase,
+}
+
+impl ColumnRef {
+    pub fn new(arena: &Arena, column: &str) -> Self {
+        ColumnRef {
+            arena: arena.name.clone(),
+            column: column.parse().unwrap(),
+        }
+    }
+
+    fn get_path(&self) -> String {
+        let arena: SnakeCase = self.arena.clone().into();
+        format!("self.{}.{}", arena, self.column)
+    }
+}
+
+/// A user-defined function that is called by the generated `run_systems` with the columns it declares,
+/// reads first and then writes, each in the order they were added.
+#[derive(Debug, Clone)]
+pub struct System {
+    pub name: SnakeCase,
+    pub reads: Vec<ColumnRef>,
+    pub writes: Vec<ColumnRef>,
+}
+
+impl System {
+    pub fn new(name: &str) -> Self {
+        System {
+            name: name.parse().unwrap(),
+            reads: vec![],
+            writes: vec![],
+        }
+    }
+
+    pub fn reads(mut self, arena: &Arena, column: &str) -> Self {
+        self.reads.push(ColumnRef::new(arena, column));
+        self
+    }
+
+    pub fn writes(mut self, arena: &Arena, column: &str) -> Self {
+        self.writes.push(ColumnRef::new(arena, column));
+        self
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item=&ColumnRef> {
+        self.reads.iter().chain(self.writes.iter())
+    }
+
+    /// True if the system must run before `other`: it writes a column that `other` reads,
+    /// or both write the same column and this system was declared first.
+    pub fn runs_before(&self, other: &System, declared_first: bool) -> bool {
+        let writes_read = self.writes.iter().any(|c| other.reads.contains(c));
+        let writes_written = self.writes.iter().any(|c| other.writes.contains(c));
+
+        writes_read || (writes_written && declared_first)
+    }
+
+    /// The call from `State::run_systems`. Each arena the system writes is borrowed mutably once, up front,
+    /// as a shared arena hands out its columns through a single `&mut`.
+    pub fn get_call(&self) -> Vec<CodeLine> {
+        let mut written: Vec<SnakeCase> = vec![];
+
+        for column in self.writes.iter() {
+            let arena: SnakeCase = column.arena.clone().into();
+
+            if !written.contains(&arena) {
+                written.push(arena);
+            }
+        }
+
+        let path = |column: &ColumnRef| {
+            let arena: SnakeCase = column.arena.clone().into();
+
+            match written.contains(&arena) {
+                true => format!("{}.{}", arena, column.column),
+                false => column.get_path(),
+            }
+        };
+
+        let reads = self.reads.iter().map(|c| format!("&{}", path(c)));
+        let writes = self.writes.iter().map(|c| format!("&mut {}", path(c)));
+        let arguments = reads.chain(writes).collect::<Vec<_>>().join(", ");
+        let call = format!("{}({});", self.name, arguments);
+
+        if written.is_empty() {
+            return vec![CodeLine::new(0, &call)];
+        }
+
+        let mut lines = vec![CodeLine::new(0, "{")];
+
+        for arena in written.iter() {
+            lines.push(CodeLine::new(1, &format!("let {a} = &mut *self.{a};", a=arena)));
+        }
+
+        lines.push(CodeLine::new(1, &call));
+        lines.push(CodeLine::new(0, "}"));
+        lines
+    }
+}
@@ -0,0 +1,149 @@
+use crate::*;
+use code_gen::{CamelCase, Trait, TraitImplementation, TraitFunction, TypeName, CodeLine};
+
+/// A set of components shared by several arenas. Applying the template adds its columns to an arena,
+/// and if the template has a trait, the arena struct implements it with an accessor for each column.
+#[derive(Debug, Clone)]
+pub struct ArenaTemplate {
+    pub name: CamelCase,
+    pub components: Vec<ComponentType>,
+    pub default_components: Vec<ComponentType>,
+    pub trait_name: Option<CamelCase>,
+}
+
+impl ArenaTemplate {
+    pub fn new(name: &str) -> Self {
+        ArenaTemplate {
+            name: name.parse().unwrap(),
+            components: vec![],
+            default_components: vec![],
+            trait_name: None,
+        }
+    }
+
+    pub fn add_component(mut self, component: ComponentType) -> Self {
+        self.components.push(component);
+        self
+    }
+
+    pub fn add_default_component(mut self, component: ComponentType) -> Self {
+        self.default_components.push(component);
+        self
+    }
+
+    /// Generates a trait, e.g. `HasPosition`, that every arena using the template implements.
+    pub fn with_trait(mut self, trait_name: &str) -> Self {
+        self.trait_name = Some(trait_name.parse().unwrap());
+        self
+    }
+
+    fn all_components(&self) -> impl Iterator<Item=&ComponentType> {
+        self.components.iter().chain(self.default_components.iter())
+    }
+
+    /// Tags have no mutable accessor, and neither do indexed components, as writing through one would bypass the index.
+    fn mutable_components(&self) -> impl Iterator<Item=&ComponentType> {
+        self.all_components()
+            .filter(|c| c.storage != Storage::Tag && c.index.is_none())
+    }
+
+    /// `Id` is the id type that indexes the arena: `Id<Self>` or `Valid<Self>`.
+    pub fn get_trait(&self) -> Option<Trait> {
+        let trait_name = self.trait_name.as_ref()?;
+        let mut definition = Trait::new(trait_name.as_str())
+            .add_associated_type("Id");
+
+        for component in self.all_components() {
+            let data_type = component.storage.get_row_data_type(&component.data_type);
+
+            definition = definition.add_function_definition(
+                TraitFunction::new(component.name.as_str())
+                    .with_parameters("&self, id: &Self::Id")
+                    .with_return(format!("&{}", data_type))
+            );
+        }
+
+        for component in self.mutable_components() {
+            let data_type = component.storage.get_row_data_type(&component.data_type);
+
+            definition = definition.add_function_definition(
+                TraitFunction::new(&format!("{}_mut", component.name))
+                    .with_parameters("&mut self, id: &Self::Id")
+                    .with_return(format!("&mut {}", data_type))
+            );
+        }
+
+        Some(definition)
+    }
+
+    pub fn get_implementation(&self, world: &World, arena: &Arena) -> Option<TraitImplementation> {
+        let mut implementation = self.get_trait()?
+            .impl_for(&arena.get_struct(world))
+            .add_associated_type(TypeName::new("Id"), arena.get_valid_id_type());
+
+        for component in self.all_components() {
+            let data_type = component.storage.get_row_data_type(&component.data_type);
+
+            implementation = implementation.add_function(
+                TraitFunction::new(component.name.as_str())
+                    .with_parameters("&self, id: &Self::Id")
+                    .with_return(format!("&{}", data_type))
+                    .add_line(CodeLine::new(0, &format!("&self.{}[id]", component.name)))
+            );
+        }
+
+        for component in self.mutable_components() {
+            let data_type = component.storage.get_row_data_type(&component.data_type);
+
+            implementation = implementation.add_function(
+                TraitFunction::new(&format!("{}_mut", component.name))
+                    .with_parameters("&mut self, id: &Self::Id")
+                    .with_return(format!("&mut {}", data_type))
+                    .add_line(CodeLine::new(0, &format!("&mut self.{}[id]", component.name)))
+            );
+        }
+
+        Some(implementation)
+    }
+
+    /// Templates sharing a trait must agree on its accessors.
+    pub fn matches(&self, other: &ArenaTemplate) -> bool {
+        let signature = |template: &ArenaTemplate| template.all_components()
+            .map(|c| (c.name.to_string(), c.data_type.to_string(), c.storage, c.index))
+            .collect::<Vec<_>>();
+
+        signature(self) == signature(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexed_components_and_tags_have_no_mut_accessor() {
+        let named = ArenaTemplate::new("Named")
+            .add_component(ComponentType::dense("name", "String").unique())
+            .add_component(ComponentType::dense("class", "u8").indexed())
+            .add_component(ComponentType::tag("landmark"))
+            .add_component(ComponentType::dense_from_type("Position"))
+            .with_trait("HasName");
+
+        let mutable = named.mutable_components()
+            .map(|c| c.name.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec!["position"], mutable);
+    }
+
+    #[test]
+    fn templates_differing_in_index_do_not_match() {
+        let indexed = ArenaTemplate::new("Named")
+            .add_component(ComponentType::dense("name", "String").unique());
+
+        let plain = ArenaTemplate::new("Labelled")
+            .add_component(ComponentType::dense("name", "String"));
+
+        assert!(!indexed.matches(&plain));
+    }
+}
@@ -4,11 +4,13 @@ pub trait Insert<ID, T> {
     fn insert(&mut self, id: &ID, value: T);
 }
 
+/// Links two entities. `Edge` is the data stored with the link, or `()` for links without edge data.
 pub trait Link<A, B> {
     type IdA;
     type IdB;
+    type Edge;
 
-    fn link(&mut self, a: &Self::IdA, b: &Self::IdB);
+    fn link(&mut self, a: &Self::IdA, b: &Self::IdB, edge: Self::Edge);
 }
 
 pub fn get_link_trait() -> Trait {
@@ -16,8 +18,25 @@ pub fn get_link_trait() -> Trait {
         .with_generics(Generics::two("A", "B"))
         .add_associated_type("IdA")
         .add_associated_type("IdB")
+        .add_associated_type("Edge")
         .add_function_definition(
-            TraitFunction::new("link").with_parameters("&mut self, a: &Self::IdA, b: &Self::IdB"))
+            TraitFunction::new("link").with_parameters("&mut self, a: &Self::IdA, b: &Self::IdB, edge: Self::Edge"))
+}
+
+pub trait Unlink<A, B> {
+    type IdA;
+    type IdB;
+
+    fn unlink(&mut self, a: &Self::IdA, b: &Self::IdB);
+}
+
+pub fn get_unlink_trait() -> Trait {
+    Trait::new("Unlink")
+        .with_generics(Generics::two("A", "B"))
+        .add_associated_type("IdA")
+        .add_associated_type("IdB")
+        .add_function_definition(
+            TraitFunction::new("unlink").with_parameters("&mut self, a: &Self::IdA, b: &Self::IdB"))
 }
 
 pub trait Create<T> {
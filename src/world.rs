@@ -14,6 +14,11 @@ pub struct World {
     pub components: Vec<StaticComponent>,
     pub links: HashMap<links::Link, LinkType>,
     pub compound_entities: Vec<CompoundEntity>,
+    pub systems: Vec<System>,
+    pub relations: Vec<Relation>,
+    pub pending_links: Vec<PluginLink>,
+    pub link_conflicts: Vec<String>,
+    pub journal: bool,
 }
 
 impl Display for World {
@@ -28,18 +33,55 @@ impl Display for World {
         writeln!(f, "{}", self.get_world()).ok();
         writeln!(f, "{}", self.impl_world()).ok();
         writeln!(f, "{}", self.get_allocators()).ok();
+        writeln!(f, "{}", self.get_capacities()).ok();
+        writeln!(f, "{}", self.get_stats()).ok();
+        writeln!(f, "{}", get_duplicates_struct(&self)).ok();
         writeln!(f, "{}", self.get_state()).ok();
+        writeln!(f, "{}", self.impl_state()).ok();
+        writeln!(f, "{}", self.get_state_split()).ok();
 
         for arena in self.arenas.iter() {
             writeln!(f, "{}", arena.get_struct(&self)).ok();
-            writeln!(f, "{}", arena.get_impl()).ok();
+
+            if arena.events {
+                writeln!(f, "{}", get_link_enum(&self, arena)).ok();
+            }
+            writeln!(f, "{}", arena.get_split_struct(&self)).ok();
+
+            for split in arena.splits.iter() {
+                writeln!(f, "{}", split.get_struct(&self, arena)).ok();
+            }
+            writeln!(f, "{}", self.impl_arena(arena)).ok();
             writeln!(f, "{}", arena.get_data_row()).ok();
+            writeln!(f, "{}", impl_data_row(arena)).ok();
+            writeln!(f, "{}", get_row_builder(arena)).ok();
+            writeln!(f, "{}", impl_row_builder(arena)).ok();
+
+            if self.journal {
+                writeln!(f, "{}", get_entity_struct(&self, arena)).ok();
+                writeln!(f, "{}", impl_entity(&self, arena)).ok();
+            }
         }
 
         for link_impl in self.get_link_implementations() {
             writeln!(f, "{}", link_impl).ok();
         }
 
+        for unlink_impl in self.get_unlink_implementations() {
+            writeln!(f, "{}", unlink_impl).ok();
+        }
+
+        for template_trait in self.get_template_traits() {
+            writeln!(f, "{}", template_trait).ok();
+        }
+
+        for template_impl in self.get_template_implementations() {
+            writeln!(f, "{}", template_impl).ok();
+        }
+
+        writeln!(f, "{}", get_commands(&self)).ok();
+        writeln!(f, "{}", impl_commands(&self)).ok();
+
         for compound in self.compound_entities.iter() {
 
         }
@@ -56,6 +98,11 @@ impl World {
             components: vec![],
             links: Default::default(),
             compound_entities: vec![],
+            systems: vec![],
+            relations: vec![],
+            pending_links: vec![],
+            link_conflicts: vec![],
+            journal: false,
         }
     }
 
@@ -66,25 +113,359 @@ impl World {
 
     pub fn add_arena(mut self, arena: Arena) -> Self {
         self.arenas.push(arena);
+        self.resolve_pending_links();
+        self.update_links();
+        self
+    }
+
+    /// Adds the arenas, static components, systems and links of a plugin. Names defined more than once,
+    /// whether by plugins or by the World itself, are rejected when the World is validated.
+    pub fn add_plugin(mut self, plugin: WorldPlugin) -> Self {
+        for use_ref in plugin.uses {
+            if !self.uses.contains(&use_ref) {
+                self.uses.push(use_ref);
+            }
+        }
+
+        self.arenas.extend(plugin.arenas);
+        self.components.extend(plugin.components);
+        self.systems.extend(plugin.systems);
+        self.pending_links.extend(plugin.links);
+
+        self.resolve_pending_links();
         self.update_links();
         self
     }
 
+    /// Applies the plugin links whose `from` arena has been added, recording those that conflict with existing links.
+    fn resolve_pending_links(&mut self) {
+        let pending = std::mem::take(&mut self.pending_links);
+
+        for link in pending {
+            match self.arenas.iter_mut().find(|a| a.name == link.from) {
+                Some(arena) => {
+                    if let Err(conflict) = link.apply(arena) {
+                        self.link_conflicts.push(conflict);
+                    }
+                },
+                None => self.pending_links.push(link),
+            }
+        }
+    }
+
     pub fn add_static_component(mut self, component: StaticComponent) -> Self {
         self.components.push(component);
         self
     }
 
+    pub fn add_system(mut self, system: System) -> Self {
+        self.systems.push(system);
+        self
+    }
+
+    /// Adds a many-to-many relation between two arenas that have already been added.
+    pub fn add_relation(mut self, relation: Relation) -> Self {
+        self.relations.push(relation);
+        self
+    }
+
+    /// Generates journaled versions of the World mutations, which can be reverted with `World::undo`.
+    pub fn add_journal(mut self) -> Self {
+        self.journal = true;
+        self
+    }
+
     pub fn get_world(&self) -> Struct {
-        Struct::new("World")
+        let world = Struct::new("World")
             .with_derives(Derives::with_debug_default_clone())
             .add_field(Field::from_type(Type::new("Allocators")))
-            .add_field(Field::from_type(Type::new("State")))
+            .add_field(Field::from_type(Type::new("State")));
+
+        if self.journal {
+            world.add_field(Field {
+                visibility: Default::default(),
+                name: "journal".parse().unwrap(),
+                field_type: Type::new("Journal<World>"),
+            })
+        } else {
+            world
+        }
     }
 
     pub fn impl_world(&self) -> Impl {
-        Impl::from(&self.get_world().typ)
+        let mut world_impl = Impl::from(&self.get_world().typ)
             .add_function(self.get_split())
+            .add_function(
+                Function::new("commands")
+                    .with_parameters("&self")
+                    .with_return(String::from("Commands"))
+                    .add_line(CodeLine::new(0, "Commands::new(&self.allocators)"))
+            )
+            .add_function(
+                Function::new("apply")
+                    .with_parameters("&mut self, commands: Commands")
+                    .with_return(String::from("Result<(), CommandError>"))
+                    .add_line(CodeLine::new(0, "commands.queue.apply(self)"))
+            );
+
+        world_impl = world_impl
+            .add_function(self.get_with_capacity())
+            .add_function(self.get_shrink_to_fit())
+            .add_function(self.get_stats_function())
+            .add_function(get_check_integrity(self));
+
+        for arena in self.transient_entities() {
+            world_impl = world_impl.add_function(self.get_compact(arena));
+        }
+
+        for arena in self.arenas.iter() {
+            for filter in arena.filters.iter() {
+                world_impl = world_impl.add_function(filter.get_function(arena));
+            }
+        }
+
+        for relation in self.relations.iter() {
+            for lookup in relation.get_lookups(self) {
+                world_impl = world_impl.add_function(lookup);
+            }
+
+            if !self.journal {
+                for change in relation.get_functions(self) {
+                    world_impl = world_impl.add_function(change);
+                }
+            }
+        }
+
+        for duplicate in get_duplicate_functions(self) {
+            world_impl = world_impl.add_function(duplicate);
+        }
+
+        if self.journal {
+            for journaled in get_journaled_functions(self) {
+                world_impl = world_impl.add_function(journaled);
+            }
+        }
+
+        let double_buffered = self.arenas.iter()
+            .filter(|arena| arena.double_buffered_components().next().is_some())
+            .collect::<Vec<_>>();
+
+        if !double_buffered.is_empty() {
+            let mut swap = Function::new("swap_buffers")
+                .with_parameters("&mut self");
+
+            for arena in double_buffered {
+                swap = swap.add_line(CodeLine::new(0, &format!("self.state.{}.swap_buffers();", arena.get_state_field().name)));
+            }
+
+            world_impl = world_impl.add_function(swap);
+        }
+
+        if !self.systems.is_empty() {
+            world_impl = world_impl.add_function(
+                Function::new("run_systems")
+                    .with_parameters("&mut self")
+                    .add_line(CodeLine::new(0, "self.state.run_systems();"))
+            );
+        }
+
+        world_impl
+    }
+
+    pub fn impl_state(&self) -> Impl {
+        let mut state_impl = Impl::from(&self.get_state().typ)
+            .add_function(self.get_state_split_function());
+
+        for arena in self.arenas.iter() {
+            let field = arena.get_state_field().name;
+
+            state_impl = state_impl.add_function(
+                Function::new(&format!("split_{}", field))
+                    .with_parameters("&mut self")
+                    .with_return(format!("{}Mut<'_>", arena.name))
+                    .add_line(CodeLine::new(0, &format!("self.{}.split()", field)))
+            );
+
+            for split in arena.splits.iter() {
+                state_impl = state_impl.add_function(split.get_state_function(arena));
+            }
+        }
+
+        let mut links: Vec<_> = self.links.keys().collect();
+        links.sort_by_key(|link| (link.from.to_string(), link.to.to_string()));
+
+        for link in links {
+            if let Some(set_edge) = link.get_set_edge(self) {
+                state_impl = state_impl.add_function(set_edge);
+            }
+        }
+
+        for arena in self.transient_entities() {
+            if let Some(kill) = self.get_state_kill(arena) {
+                state_impl = state_impl.add_function(kill);
+            }
+        }
+
+        if !self.systems.is_empty() {
+            let mut run_systems = Function::new("run_systems")
+                .with_parameters("&mut self");
+
+            for system in self.get_system_order() {
+                for line in system.get_call() {
+                    run_systems = run_systems.add_line(line);
+                }
+            }
+
+            state_impl = state_impl.add_function(run_systems);
+        }
+
+        state_impl
+    }
+
+    /// The arena impl, with a `kill` only if killing an entity needs no cleanup outside the arena.
+    pub fn impl_arena(&self, arena: &Arena) -> Impl {
+        let mut arena_impl = arena.get_impl();
+
+        let mut edges: Vec<_> = arena.edges.iter()
+            .filter(|(link_to, _)| {
+                let other = self.get_arena(link_to);
+                !other.owns(arena) && !other.references(arena)
+            })
+            .collect();
+        edges.sort_by_key(|(link_to, _)| link_to.to_string());
+
+        for (link_to, edge) in edges {
+            arena_impl = arena_impl.add_function(arena.get_set_edge(link_to, edge));
+        }
+
+        if arena.allocator == Allocator::Generational && self.get_state_kill(arena).is_none() {
+            arena_impl.add_function(arena.get_kill())
+        } else {
+            arena_impl
+        }
+    }
+
+    /// Kills an entity, removing it from the relations it takes part in and clearing the optional links
+    /// that point at it. Only generated for arenas that need this cleanup, which have no `kill` of their own.
+    fn get_state_kill(&self, arena: &Arena) -> Option<Function> {
+        let relation_lines = self.relations.iter()
+            .flat_map(|relation| relation.get_cleanup_lines(arena))
+            .map(|line| (0, line));
+
+        let backlink_lines = get_backlinks(self)
+            .into_iter()
+            .flat_map(|backlink| backlink.get_kill_lines(self, arena));
+
+        let cleanup = relation_lines
+            .chain(backlink_lines)
+            .collect::<Vec<_>>();
+
+        if cleanup.is_empty() {
+            return None;
+        }
+
+        let field = arena.get_state_field().name;
+
+        let alive = match arena.has_release() {
+            true => format!("if let Some(valid) = allocators.{}.verify(id) {{", field),
+            false => format!("if allocators.{}.is_alive(id) {{", field),
+        };
+
+        let mut kill = Function::new(&format!("kill_{}", field))
+            .with_parameters(&format!("&mut self, id: GenId<{}>, allocators: &mut Allocators", arena.name))
+            .add_line(CodeLine::new(0, &alive));
+
+        for (indent, line) in cleanup {
+            kill = kill.add_line(CodeLine::new(indent + 1, &line));
+        }
+
+        if arena.has_release() {
+            kill = kill.add_line(CodeLine::new(1, &format!("self.{}.release(valid);", field)));
+        }
+
+        if arena.events {
+            kill = kill.add_line(CodeLine::new(1, &format!("self.{}.events.push(Event::Killed(id));", field)));
+        }
+
+        kill
+            .add_line(CodeLine::new(0, "}"))
+            .add_line(CodeLine::new(0, &format!("allocators.{}.kill(id);", field)))
+            .into()
+    }
+
+    /// The call that kills `id` in the arena, going through the State if the arena needs cleanup elsewhere.
+    pub fn get_kill_call(&self, arena: &Arena, world: &str) -> String {
+        let field = arena.get_state_field().name;
+
+        if self.get_state_kill(arena).is_some() {
+            format!("{w}.state.kill_{f}(id, &mut {w}.allocators)", w=world, f=field)
+        } else {
+            format!("{w}.state.{f}.kill(id, &mut {w}.allocators.{f})", w=world, f=field)
+        }
+    }
+
+    /// Borrows every static component and arena column of the State at once.
+    pub fn get_state_split(&self) -> Struct {
+        let static_fields = self.components.iter()
+            .map(StaticComponent::get_field)
+            .map(|f| Field {
+                field_type: Type::new(&format!("&'a mut {}", f.field_type)),
+                ..f
+            });
+
+        let arena_fields = self.arenas.iter()
+            .map(|arena| Field {
+                visibility: Visibility::Pub,
+                name: arena.get_state_field().name,
+                field_type: arena.get_split_type(),
+            });
+
+        Struct {
+            typ: Type::new("StateMut<'a>"),
+            visibility: Default::default(),
+            derives: Default::default(),
+            fields: static_fields.chain(arena_fields).collect(),
+        }
+    }
+
+    fn get_state_split_function(&self) -> Function {
+        let mut split = Function::new("split")
+            .with_parameters("&mut self")
+            .with_return(String::from("StateMut<'_>"))
+            .add_line(CodeLine::new(0, "StateMut {"));
+
+        for component in self.components.iter() {
+            split = split.add_line(CodeLine::new(1, &format!("{name}: &mut self.{name},", name=component.name)));
+        }
+
+        for arena in self.arenas.iter() {
+            split = split.add_line(CodeLine::new(1, &format!("{name}: self.{name}.split(),", name=arena.get_state_field().name)));
+        }
+
+        split.add_line(CodeLine::new(0, "}"))
+    }
+
+    /// Orders systems so that writers of a column run before its readers, otherwise keeping declaration order.
+    fn get_system_order(&self) -> Vec<&System> {
+        let count = self.systems.len();
+        let mut placed = vec![false; count];
+        let mut order = Vec::with_capacity(count);
+
+        while order.len() < count {
+            let next = (0..count)
+                .filter(|i| !placed[*i])
+                .find(|i| {
+                    (0..count)
+                        .filter(|j| !placed[*j] && j != i)
+                        .all(|j| !self.systems[j].runs_before(&self.systems[*i], j < *i))
+                })
+                .unwrap_or_else(|| panic!("Systems have a cyclic column dependency"));
+
+            placed[next] = true;
+            order.push(&self.systems[next]);
+        }
+
+        order
     }
 
     fn get_split(&self) -> Function {
@@ -117,6 +498,138 @@ impl World {
             .add_line(CodeLine::new(0, &code))
     }
 
+    /// Per-arena capacity hints for `World::with_capacity`.
+    pub fn get_capacities(&self) -> Struct {
+        let fields = self.arenas.iter()
+            .map(|arena| Field {
+                visibility: Visibility::Pub,
+                name: arena.get_state_field().name,
+                field_type: Type::new("usize"),
+            })
+            .collect();
+
+        Struct::new("Capacities")
+            .with_derives(Derives::with_debug_default_clone())
+            .with_fields(fields)
+    }
+
+    fn get_with_capacity(&self) -> Function {
+        let mut with_capacity = Function::new("with_capacity")
+            .with_parameters("capacities: Capacities")
+            .with_return(String::from("Self"))
+            .add_line(CodeLine::new(0, "let mut world = Self::default();"));
+
+        for arena in self.arenas.iter() {
+            let name = arena.get_state_field().name;
+
+            with_capacity = with_capacity.add_line(CodeLine::new(0, &format!("world.allocators.{name}.reserve(capacities.{name});", name=name)));
+            with_capacity = with_capacity.add_line(CodeLine::new(0, &format!("world.state.{name}.reserve(capacities.{name});", name=name)));
+        }
+
+        with_capacity.add_line(CodeLine::new(0, "world"))
+    }
+
+    fn get_shrink_to_fit(&self) -> Function {
+        let mut shrink = Function::new("shrink_to_fit")
+            .with_parameters("&mut self");
+
+        for arena in self.arenas.iter() {
+            let name = arena.get_state_field().name;
+
+            shrink = shrink.add_line(CodeLine::new(0, &format!("self.allocators.{}.shrink_to_fit();", name)));
+            shrink = shrink.add_line(CodeLine::new(0, &format!("self.state.{}.shrink_to_fit();", name)));
+        }
+
+        shrink
+    }
+
+    /// Per-arena allocator counts and column memory returned by `World::stats`.
+    pub fn get_stats(&self) -> Struct {
+        let fields = self.arenas.iter()
+            .map(|arena| Field {
+                visibility: Visibility::Pub,
+                name: arena.get_state_field().name,
+                field_type: Type::new("ArenaStats"),
+            })
+            .collect();
+
+        Struct::new("Stats")
+            .with_derives(Derives::with_debug_default_clone())
+            .with_fields(fields)
+    }
+
+    fn get_stats_function(&self) -> Function {
+        let mut stats = Function::new("stats")
+            .with_parameters("&self")
+            .with_return(String::from("Stats"))
+            .add_line(CodeLine::new(0, "Stats {"));
+
+        for arena in self.arenas.iter() {
+            let name = arena.get_state_field().name;
+
+            stats = stats
+                .add_line(CodeLine::new(1, &format!("{}: ArenaStats {{", name)))
+                .add_line(CodeLine::new(2, &format!("allocator: self.allocators.{}.stats(),", name)))
+                .add_line(CodeLine::new(2, &format!("memory: self.state.{}.memory(),", name)))
+                .add_line(CodeLine::new(1, "},"));
+        }
+
+        stats.add_line(CodeLine::new(0, "}"))
+    }
+
+    /// Compacts a generational arena and rewrites every link column that points at it.
+    /// Compaction moves ids, so the recorded operations of a journaled World are cleared and cannot be undone.
+    fn get_compact(&self, arena: &Arena) -> Function {
+        let name = arena.get_state_field().name;
+
+        let mut compact = Function::new(&format!("compact_{}", name))
+            .with_parameters("&mut self")
+            .with_return(format!("Remap<{}>", arena.name))
+            .add_line(CodeLine::new(0, &format!("let remap = self.allocators.{}.compact();", name)))
+            .add_line(CodeLine::new(0, &format!("self.state.{}.compact(&remap);", name)));
+
+        let linking_arenas = self.arenas.iter()
+            .filter(|a| a.owns(arena) || a.references(arena));
+
+        for linking in linking_arenas {
+            compact = compact.add_line(CodeLine::new(0, &format!(
+                "self.state.{}.{}.remap_ids(&remap);",
+                linking.get_state_field().name,
+                name,
+            )));
+        }
+
+        for line in self.relations.iter().flat_map(|relation| relation.get_remap_lines(arena)) {
+            compact = compact.add_line(CodeLine::new(0, &line));
+        }
+
+        // link events of other arenas hold ids of this one
+        let linked_with_events = get_linked_arenas(self, arena)
+            .into_iter()
+            .filter(|linked| linked.events);
+
+        for linked in linked_with_events {
+            compact = compact
+                .add_line(CodeLine::new(0, "#[allow(irrefutable_let_patterns)]"))
+                .add_line(CodeLine::new(0, &format!(
+                    "self.state.{}.events.remap_links(|link| if let {}::{}(id) = link {{ if let Some(new) = remap.get(*id) {{ *id = new; }} }});",
+                    linked.get_state_field().name,
+                    get_link_enum_name(linked),
+                    arena.name,
+                )));
+        }
+
+        for line in get_backlinks(self).iter().flat_map(|backlink| backlink.get_remap_lines(arena)) {
+            compact = compact.add_line(CodeLine::new(0, &line));
+        }
+
+        if self.journal {
+            compact = compact.add_line(CodeLine::new(0, "self.journal.clear();"));
+        }
+
+        compact.add_line(CodeLine::new(0, "remap"))
+    }
+
     pub fn get_allocators(&self) -> Struct {
         let fields = self.arenas.iter()
             .map(Arena::get_allocator_field)
@@ -130,9 +643,13 @@ impl World {
     pub fn get_state(&self) -> Struct {
         let static_fields = self.components.iter().map(StaticComponent::get_field);
         let arena_fields = self.arenas.iter().map(Arena::get_state_field);
+        let relation_fields = self.relations.iter().map(|relation| relation.get_state_field(self));
+        let backlink_fields = get_backlinks(self).into_iter().map(|backlink| backlink.get_state_field(self));
 
         let fields = static_fields
             .chain(arena_fields)
+            .chain(relation_fields)
+            .chain(backlink_fields)
             .collect();
 
         Struct::new("State")
@@ -141,9 +658,132 @@ impl World {
     }
 
     fn validate(&self) {
+        assert!(self.definitions_are_distinct());
+        assert!(self.plugin_links_agree(), "{}", self.link_conflicts.join("\n"));
         assert!(self.no_transient_owns_permanent());
         assert!(self.no_permanent_has_mandatory_link_to_transient());
         assert!(self.no_transient_has_mandatory_reference_to_non_owner_transient());
+        assert!(self.systems_access_existing_columns());
+        assert!(self.systems_access_each_column_once());
+        assert!(self.filters_use_presence_columns());
+        assert!(self.splits_borrow_distinct_columns());
+        assert!(self.relations_have_distinct_names());
+        assert!(self.link_edges_match());
+        assert!(self.plugin_links_resolved());
+        assert!(self.templates_are_consistent());
+        assert!(self.no_component_named_build());
+    }
+
+    /// Row builders have a setter for each component next to their `build` method, so no component can take that name.
+    fn no_component_named_build(&self) -> bool {
+        self.arenas.iter()
+            .flat_map(|arena| arena.components.iter())
+            .all(|component| component.name.as_str() != "build")
+    }
+
+    /// An arena applies each template once, and templates declaring the same trait declare the same accessors.
+    fn templates_are_consistent(&self) -> bool {
+        let templates = self.arenas.iter()
+            .flat_map(|arena| arena.templates.iter())
+            .collect::<Vec<_>>();
+
+        let applied_once = self.arenas.iter().all(|arena| {
+            arena.templates.iter()
+                .enumerate()
+                .all(|(i, template)| arena.templates[..i].iter().all(|t| t.name != template.name))
+        });
+
+        let traits_match = templates.iter().all(|a| {
+            templates.iter()
+                .filter(|b| a.trait_name.is_some() && b.trait_name == a.trait_name)
+                .all(|b| a.matches(b))
+        });
+
+        applied_once && traits_match
+    }
+
+    fn plugin_links_resolved(&self) -> bool {
+        self.pending_links.is_empty()
+    }
+
+    /// Both columns of a two-way link are written by the same `Link::link` call, so they must store the same edge data.
+    fn link_edges_match(&self) -> bool {
+        self.arenas.iter().all(|arena| {
+            arena.edges.keys().all(|link_to| arena.ownership.contains_key(link_to) || arena.references.contains_key(link_to))
+                && arena.references.keys()
+                    .map(|other| self.get_arena(other))
+                    .filter(|other| other.owns(arena) || other.references(arena))
+                    .all(|other| {
+                        let edge = arena.get_edge_type(&other.name).map(ToString::to_string);
+                        let reverse = other.get_edge_type(&arena.name).map(ToString::to_string);
+                        edge == reverse
+                    })
+        })
+    }
+
+    /// Arenas, static components and systems are each defined once, however many plugins they come from.
+    fn definitions_are_distinct(&self) -> bool {
+        fn distinct<T: PartialEq>(names: &[T]) -> bool {
+            names.iter().enumerate().all(|(i, name)| !names[..i].contains(name))
+        }
+
+        distinct(&self.arenas.iter().map(|a| &a.name).collect::<Vec<_>>())
+            && distinct(&self.components.iter().map(|c| &c.name).collect::<Vec<_>>())
+            && distinct(&self.systems.iter().map(|s| &s.name).collect::<Vec<_>>())
+    }
+
+    /// Plugins and the arenas they link do not declare the same link in different ways.
+    fn plugin_links_agree(&self) -> bool {
+        self.link_conflicts.is_empty()
+    }
+
+    /// Relations are State fields, so their names cannot be taken by static components, arenas or backlinks.
+    fn relations_have_distinct_names(&self) -> bool {
+        let mut names = self.components.iter()
+            .map(|c| c.name.clone())
+            .chain(self.arenas.iter().map(|a| a.get_state_field().name))
+            .chain(get_backlinks(self).iter().map(Backlink::get_name))
+            .collect::<Vec<_>>();
+
+        self.relations.iter().all(|relation| {
+            self.get_arena(&relation.from);
+            self.get_arena(&relation.to);
+
+            let distinct = !names.contains(&relation.name);
+            names.push(relation.name.clone());
+            distinct
+        })
+    }
+
+    fn splits_borrow_distinct_columns(&self) -> bool {
+        self.arenas.iter()
+            .all(|arena| arena.splits.iter().all(|split| split.is_valid(self, arena)))
+    }
+
+    fn filters_use_presence_columns(&self) -> bool {
+        self.arenas.iter()
+            .all(|arena| {
+                let columns = arena.get_presence_column_names(self);
+
+                arena.filters.iter()
+                    .all(|f| !f.columns.is_empty() && f.columns.iter().all(|c| columns.contains(c)))
+            })
+    }
+
+    fn systems_access_existing_columns(&self) -> bool {
+        self.systems.iter()
+            .flat_map(System::columns)
+            .all(|c| self.get_arena(&c.arena).has_column(&c.column))
+    }
+
+    fn systems_access_each_column_once(&self) -> bool {
+        self.systems.iter()
+            .all(|system| {
+                let columns: Vec<&ColumnRef> = system.columns().collect();
+                columns.iter()
+                    .enumerate()
+                    .all(|(i, c)| !columns[i + 1..].contains(c))
+            })
     }
 
     fn no_transient_owns_permanent(&self) -> bool {
@@ -221,6 +861,38 @@ impl World {
         map
     }
 
+    pub fn get_unlink_implementations(&self) -> Vec<TraitImplementation> {
+        self.links.iter()
+            .filter_map(|(link, link_type)| {
+                link.get_unlink_implementation(self, link_type)
+            })
+            .collect()
+    }
+
+    /// Each trait is generated once, however many arenas apply the templates that declare it.
+    pub fn get_template_traits(&self) -> Vec<Trait> {
+        let mut names = vec![];
+
+        self.arenas.iter()
+            .flat_map(|arena| arena.templates.iter())
+            .filter(|template| match &template.trait_name {
+                Some(name) if !names.contains(name) => {
+                    names.push(name.clone());
+                    true
+                },
+                _ => false,
+            })
+            .filter_map(ArenaTemplate::get_trait)
+            .collect()
+    }
+
+    pub fn get_template_implementations(&self) -> Vec<TraitImplementation> {
+        self.arenas.iter()
+            .flat_map(|arena| arena.templates.iter()
+                .filter_map(move |template| template.get_implementation(self, arena)))
+            .collect()
+    }
+
     pub fn get_link_implementations(&self) -> Vec<TraitImplementation> {
         self.links.iter()
             .filter_map(|(link, link_type)| {
@@ -376,6 +1048,619 @@ mod tests {
         invalid.validate();
     }
 
+    #[test]
+    #[should_panic]
+    fn invalid_system_accessing_missing_column() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense_from_type("Position"));
+
+        let system = System::new("update_orbits")
+            .writes(&body, "velocity");
+
+        let invalid = World::new()
+            .add_arena(body)
+            .add_system(system);
+
+        invalid.validate();
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_system_reading_and_writing_same_column() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense_from_type("Position"));
+
+        let system = System::new("update_orbits")
+            .reads(&body, "position")
+            .writes(&body, "position");
+
+        let invalid = World::new()
+            .add_arena(body)
+            .add_system(system);
+
+        invalid.validate();
+    }
+
+    #[test]
+    fn system_order_runs_writers_before_readers() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense_from_type("Position"))
+            .add_component(ComponentType::dense("parameters", "OrbitParameters"));
+
+        let draw = System::new("draw_bodies")
+            .reads(&body, "position");
+
+        let orbit = System::new("update_orbits")
+            .reads(&body, "parameters")
+            .writes(&body, "position");
+
+        let world = World::new()
+            .add_arena(body)
+            .add_system(draw)
+            .add_system(orbit);
+
+        let order: Vec<String> = world.get_system_order()
+            .iter()
+            .map(|s| s.name.to_string())
+            .collect();
+
+        assert_eq!(vec!["update_orbits", "draw_bodies"], order);
+    }
+
+    #[test]
+    #[should_panic]
+    fn system_order_panics_on_cycle() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense_from_type("Position"))
+            .add_component(ComponentType::dense_from_type("Velocity"));
+
+        let a = System::new("a")
+            .reads(&body, "position")
+            .writes(&body, "velocity");
+
+        let b = System::new("b")
+            .reads(&body, "velocity")
+            .writes(&body, "position");
+
+        let world = World::new()
+            .add_arena(body)
+            .add_system(a)
+            .add_system(b);
+
+        world.get_system_order();
+    }
+
+    #[test]
+    fn system_can_write_next_buffer() {
+        let body = Arena::fixed("Body")
+            .add_default_component(ComponentType::double_buffered("position", "Position"));
+
+        let system = System::new("update_positions")
+            .reads(&body, "position")
+            .writes(&body, "position_next");
+
+        let world = World::new()
+            .add_arena(body)
+            .add_system(system);
+
+        assert!(world.systems_access_existing_columns());
+    }
+
+    #[test]
+    fn snapshots_share_arenas_until_they_are_written() {
+        let body = Arena::fixed("Body")
+            .add_default_component(ComponentType::double_buffered("position", "Position"));
+
+        let system = System::new("update_positions")
+            .reads(&body, "position")
+            .writes(&body, "position_next");
+
+        let world = World::new()
+            .add_arena(body)
+            .add_system(system);
+
+        let allocators = world.get_allocators().to_string();
+        let state = world.get_state().to_string();
+        let state_impl = world.impl_state().to_string();
+
+        assert!(allocators.contains("pub body: Shared<FixedAllocator<Body>>"));
+        assert!(state.contains("body: Shared<Body>"));
+        assert!(state_impl.contains("let body = &mut *self.body;"));
+        assert!(state_impl.contains("update_positions(&body.position, &mut body.position_next);"));
+    }
+
+    #[test]
+    fn swapping_buffers_carries_current_values_into_the_next_buffer() {
+        let body = Arena::fixed("Body")
+            .add_default_component(ComponentType::double_buffered("position", "Position"));
+
+        let world = World::new()
+            .add_arena(body.clone());
+
+        let generated = world.impl_arena(&body).to_string();
+
+        assert!(generated.contains("std::mem::swap(&mut self.position, &mut self.position_next);"));
+        assert!(generated.contains("self.position_next.values.clone_from(&self.position.values);"));
+    }
+
+    #[test]
+    fn system_can_drain_events() {
+        let ship = Arena::generational("Ship")
+            .add_component(ComponentType::dense_from_type("Position"))
+            .add_events();
+
+        let system = System::new("handle_ship_events")
+            .writes(&ship, "events");
+
+        let world = World::new()
+            .add_arena(ship)
+            .add_system(system);
+
+        assert!(world.systems_access_existing_columns());
+    }
+
+    #[test]
+    fn relation_between_arenas() {
+        let colony = Arena::generational("Colony");
+        let fleet = Arena::generational("Fleet");
+
+        let world = World::new()
+            .add_arena(colony.clone())
+            .add_arena(fleet.clone())
+            .add_relation(Relation::new("trade_routes", &colony, &colony))
+            .add_relation(Relation::new("members", &fleet, &colony));
+
+        assert!(world.relations_have_distinct_names());
+        assert!(world.get_state_kill(&colony).is_some());
+        assert_eq!("world.state.kill_fleet(id, &mut world.allocators)", world.get_kill_call(&fleet, "world"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_relation_named_like_an_arena() {
+        let colony = Arena::generational("Colony");
+        let fleet = Arena::generational("Fleet");
+
+        let invalid = World::new()
+            .add_arena(colony.clone())
+            .add_arena(fleet.clone())
+            .add_relation(Relation::new("fleet", &fleet, &colony));
+
+        invalid.validate();
+    }
+
+    #[test]
+    fn two_way_link_with_matching_edges() {
+        let mut colony = Arena::generational("Colony");
+        let mut body = Arena::fixed("Body");
+
+        body.add_ownership_with_edge(&colony, LinkType::Optional, "LandingSite");
+        colony.add_reference_with_edge(&body, LinkType::Required, "LandingSite");
+
+        let world = World::new()
+            .add_arena(body)
+            .add_arena(colony);
+
+        assert!(world.link_edges_match());
+    }
+
+    #[test]
+    fn two_way_edges_are_set_on_both_sides() {
+        let mut colony = Arena::generational("Colony");
+        let mut body = Arena::fixed("Body");
+        let mut ship = Arena::generational("Ship");
+
+        body.add_ownership_with_edge(&colony, LinkType::Optional, "LandingSite");
+        colony.add_reference_with_edge(&body, LinkType::Required, "LandingSite");
+        ship.add_reference_with_edge(&body, LinkType::Optional, "Orbit");
+
+        let world = World::new()
+            .add_arena(body.clone())
+            .add_arena(colony.clone())
+            .add_arena(ship.clone());
+
+        let state = world.impl_state().to_string();
+
+        assert!(state.contains("fn set_body_colony_edge(&mut self, a: &Id<Body>, b: &Valid<Colony>, edge: LandingSite)"));
+        assert!(state.contains("*current = edge.clone();"));
+        assert!(state.contains("let link = &mut self.colony.body[b];"));
+        assert!(state.contains("link.1 = edge;"));
+        assert!(!state.contains("fn set_colony_body_edge("));
+
+        assert!(!world.impl_arena(&colony).to_string().contains("fn set_body_edge("));
+        assert!(world.impl_arena(&ship).to_string().contains("fn set_body_edge("));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_two_way_link_with_edge_on_one_side() {
+        let mut colony = Arena::generational("Colony");
+        let mut body = Arena::fixed("Body");
+
+        body.add_ownership(&colony, LinkType::Optional);
+        colony.add_reference_with_edge(&body, LinkType::Required, "LandingSite");
+
+        let invalid = World::new()
+            .add_arena(body)
+            .add_arena(colony);
+
+        invalid.validate();
+    }
+
+    #[test]
+    fn killing_clears_optional_references() {
+        let controller = Arena::generational("Controller");
+        let mut ship = Arena::generational("Ship");
+        ship.add_reference(&controller, LinkType::Optional);
+
+        let world = World::new()
+            .add_arena(controller.clone())
+            .add_arena(ship.clone());
+
+        let backlinks = get_backlinks(&world);
+
+        assert_eq!(1, backlinks.len());
+        assert_eq!("ship_controller_backlinks", backlinks[0].get_name().to_string());
+        assert!(world.get_state_kill(&controller).is_some());
+        assert!(world.get_state_kill(&ship).is_some());
+    }
+
+    #[test]
+    fn kill_with_cleanup_clears_linking_column() {
+        let controller = Arena::generational("Controller").add_events();
+        let mut ship = Arena::generational("Ship");
+        ship.add_reference(&controller, LinkType::Optional);
+
+        let world = World::new()
+            .add_arena(controller.clone())
+            .add_arena(ship);
+
+        let state = world.impl_state().to_string();
+
+        assert!(state.contains("self.ship.controller.insert(valid, None);"));
+        assert!(state.contains("self.controller.events.push(Event::Killed(id));"));
+        assert!(state.contains("allocators.controller.kill(id);"));
+        assert!(!world.impl_arena(&controller).to_string().contains("allocator.kill(id);"));
+    }
+
+    #[test]
+    fn compacting_clears_the_journal() {
+        let ship = Arena::generational("Ship");
+
+        let journaled = World::new().add_arena(ship.clone()).add_journal();
+        let plain = World::new().add_arena(ship);
+
+        assert!(journaled.impl_world().to_string().contains("self.journal.clear();"));
+        assert!(!plain.impl_world().to_string().contains("self.journal.clear();"));
+    }
+
+    #[test]
+    fn kill_without_cleanup_stays_on_the_arena() {
+        let ship = Arena::generational("Ship");
+        let world = World::new().add_arena(ship.clone());
+
+        assert!(world.get_state_kill(&ship).is_none());
+        assert!(world.impl_arena(&ship).to_string().contains("allocator.kill(id);"));
+    }
+
+    #[test]
+    fn plugin_links_resolve_across_plugins() {
+        let economy = WorldPlugin::new("economy")
+            .add_arena(Arena::generational("Colony"))
+            .add_reference("Colony", "Body", LinkType::Required)
+            .add_ownership("Body", "Colony", LinkType::Optional);
+
+        let physics = WorldPlugin::new("physics")
+            .add_arena(Arena::fixed("Body"));
+
+        let world = World::new()
+            .add_plugin(economy)
+            .add_plugin(physics);
+
+        assert!(world.plugin_links_resolved());
+        assert!(world.get_arena(&"Body".parse().unwrap()).owns(world.get_arena(&"Colony".parse().unwrap())));
+        assert_eq!(2, world.links.len());
+    }
+
+    #[test]
+    fn conflicting_plugin_links_are_recorded() {
+        let mut colony = Arena::generational("Colony");
+        let body = Arena::fixed("Body");
+        colony.add_reference(&body, LinkType::Required);
+
+        let economy = WorldPlugin::new("economy")
+            .add_reference("Colony", "Body", LinkType::Optional);
+
+        let world = World::new()
+            .add_arena(body)
+            .add_arena(colony)
+            .add_plugin(economy);
+
+        assert!(!world.plugin_links_agree());
+        assert_eq!(vec!["Conflicting links from Colony to Body".to_string()], world.link_conflicts);
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_plugin_link_conflicts_with_the_arena() {
+        let mut colony = Arena::generational("Colony");
+        let body = Arena::fixed("Body");
+        colony.add_reference(&body, LinkType::Required);
+
+        let economy = WorldPlugin::new("economy")
+            .add_reference("Colony", "Body", LinkType::Optional);
+
+        World::new()
+            .add_arena(body)
+            .add_arena(colony)
+            .add_plugin(economy)
+            .validate();
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_plugins_define_the_same_arena() {
+        let physics = WorldPlugin::new("physics")
+            .add_arena(Arena::fixed("Body"));
+
+        let astronomy = WorldPlugin::new("astronomy")
+            .add_arena(Arena::fixed("Body"));
+
+        World::new()
+            .add_plugin(physics)
+            .add_plugin(astronomy)
+            .validate();
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_plugin_defines_the_same_system_twice() {
+        let physics = WorldPlugin::new("physics")
+            .add_system(System::new("orbits"))
+            .add_system(System::new("orbits"));
+
+        World::new()
+            .add_plugin(physics)
+            .validate();
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_arena_added_after_plugin_defines_it() {
+        let physics = WorldPlugin::new("physics")
+            .add_arena(Arena::fixed("Body"));
+
+        World::new()
+            .add_plugin(physics)
+            .add_arena(Arena::fixed("Body"))
+            .validate();
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_relation_named_like_backlinks() {
+        let controller = Arena::generational("Controller");
+        let mut ship = Arena::generational("Ship");
+        ship.add_reference(&controller, LinkType::Optional);
+
+        let relation = Relation::new("ship_controller_backlinks", &ship, &controller);
+
+        World::new()
+            .add_arena(controller)
+            .add_arena(ship)
+            .add_relation(relation)
+            .validate();
+    }
+
+    #[test]
+    fn plugins_with_distinct_names_are_valid() {
+        let physics = WorldPlugin::new("physics")
+            .add_arena(Arena::fixed("Body"))
+            .add_system(System::new("orbits"));
+
+        let economy = WorldPlugin::new("economy")
+            .add_arena(Arena::generational("Colony"))
+            .add_system(System::new("trade"));
+
+        let world = World::new()
+            .add_plugin(physics)
+            .add_plugin(economy);
+
+        assert!(world.definitions_are_distinct());
+        assert!(world.relations_have_distinct_names());
+    }
+
+    #[test]
+    fn filter_accepts_sparse_links_and_tags() {
+        let surface = Arena::fixed("Surface");
+
+        let mut body = Arena::fixed("Body")
+            .add_component(ComponentType::sparse("name", "String"))
+            .add_default_component(ComponentType::tag("is_selected"))
+            .add_filter("selected_surfaces", &["surface", "is_selected"]);
+        body.add_ownership(&surface, LinkType::Optional);
+
+        let world = World::new()
+            .add_arena(surface)
+            .add_arena(body);
+
+        assert!(world.filters_use_presence_columns());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_filter_on_dense_column() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense_from_type("Position"))
+            .add_filter("positioned", &["position"]);
+
+        let invalid = World::new()
+            .add_arena(body);
+
+        invalid.validate();
+    }
+
+    #[test]
+    fn templates_share_one_trait() {
+        let located = ArenaTemplate::new("Located")
+            .add_component(ComponentType::dense_from_type("Position"))
+            .add_component(ComponentType::tag("landmark"))
+            .with_trait("HasPosition");
+
+        let world = World::new()
+            .add_arena(Arena::fixed("System").apply_template(&located))
+            .add_arena(Arena::generational("Ship").apply_template(&located));
+
+        assert!(world.templates_are_consistent());
+        assert!(world.get_arena(&"Ship".parse().unwrap()).has_column(&"position".parse().unwrap()));
+        assert_eq!(1, world.get_template_traits().len());
+        assert_eq!(2, world.get_template_implementations().len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_templates_declare_the_same_trait_differently() {
+        let located = ArenaTemplate::new("Located")
+            .add_component(ComponentType::dense_from_type("Position"))
+            .with_trait("HasPosition");
+
+        let placed = ArenaTemplate::new("Placed")
+            .add_component(ComponentType::sparse_from_type("Position"))
+            .with_trait("HasPosition");
+
+        let invalid = World::new()
+            .add_arena(Arena::fixed("System").apply_template(&located))
+            .add_arena(Arena::generational("Ship").apply_template(&placed));
+
+        invalid.validate();
+    }
+
+    #[test]
+    fn rows_derive_default_only_when_every_component_has_one() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense("mass", "f64"))
+            .add_component(ComponentType::sparse("atmosphere", "Atmosphere"))
+            .add_component(ComponentType::tag("habitable"));
+
+        let system = Arena::fixed("System")
+            .add_component(ComponentType::dense_from_type("Position"));
+
+        assert!(body.components.iter().all(ComponentType::has_default));
+        assert!(!system.components.iter().all(ComponentType::has_default));
+        assert!(!ComponentType::dense("name", "String").has_default());
+        assert!(!ComponentType::dense("name", "String").release_on_kill().has_default());
+        assert_eq!("BodyRowBuilder", get_row_builder(&body).typ.to_string());
+    }
+
+    #[test]
+    fn rows_derive_default_for_declared_types() {
+        let system = Arena::fixed("System")
+            .add_component(ComponentType::dense_from_type("Position").with_default());
+
+        assert!(system.components.iter().all(ComponentType::has_default));
+        assert_eq!(Some("Default::default()"), system.components[0].get_released_value());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_component_named_build() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense("build", "u32"));
+
+        let invalid = World::new()
+            .add_arena(body);
+
+        invalid.validate();
+    }
+
+    #[test]
+    fn split_borrows_reads_as_shared() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense_from_type("Position"))
+            .add_component(ComponentType::dense("parameters", "OrbitParameters"))
+            .add_split("orbits", &["parameters"], &["position"]);
+
+        let world = World::new()
+            .add_arena(body.clone());
+
+        let split = body.splits[0].get_struct(&world, &body);
+
+        assert!(world.splits_borrow_distinct_columns());
+        assert_eq!("BodyOrbits<'a>", split.typ.to_string());
+        assert_eq!("&'a Component<Body, OrbitParameters>", split.fields[0].field_type.to_string());
+        assert_eq!("&'a mut Component<Body, Position>", split.fields[1].field_type.to_string());
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_split_borrows_column_twice() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense_from_type("Position"))
+            .add_split("orbits", &["position"], &["position"]);
+
+        let invalid = World::new()
+            .add_arena(body);
+
+        invalid.validate();
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_split_writes_indexed_column() {
+        let body = Arena::fixed("Body")
+            .add_component(ComponentType::dense("name", "String").indexed())
+            .add_split("naming", &[], &["name"]);
+
+        let invalid = World::new()
+            .add_arena(body);
+
+        invalid.validate();
+    }
+
+    #[test]
+    fn split_can_only_read_backlinked_columns() {
+        let controller = Arena::generational("Controller");
+        let mut ship = Arena::generational("Ship")
+            .add_split("control", &["controller"], &[]);
+        ship.add_reference(&controller, LinkType::Optional);
+
+        let world = World::new()
+            .add_arena(controller.clone())
+            .add_arena(ship.clone());
+
+        assert!(world.splits_borrow_distinct_columns());
+
+        let mut ship = ship.add_split("steer", &[], &["controller"]);
+        ship.splits.remove(0);
+
+        let invalid = World::new()
+            .add_arena(controller)
+            .add_arena(ship);
+
+        assert!(!invalid.splits_borrow_distinct_columns());
+    }
+
+    #[test]
+    fn killing_releases_declared_defaults() {
+        let system = Arena::fixed("System");
+        let mut ship = Arena::generational("Ship")
+            .add_component(ComponentType::dense("name", "String").with_default())
+            .add_component(ComponentType::dense("log", "String"))
+            .add_component(ComponentType::dense("crew", "Vec<Crew>").release_on_kill())
+            .add_component(ComponentType::dense("cargo", "Vec<Crate>").keep_on_kill())
+            .add_component(ComponentType::dense_from_type("Position"));
+        ship.add_reference(&system, LinkType::Optional);
+
+        let release = ship.get_impl().to_string();
+
+        assert!(release.contains("self.name.insert(id, Default::default());"));
+        assert!(release.contains("self.crew.insert(id, Default::default());"));
+        assert!(!release.contains("self.log.insert(id, Default::default());"));
+        assert!(release.contains("self.system.insert(id, None);"));
+        assert!(!release.contains("self.cargo.insert(id, Default::default());"));
+        assert!(!release.contains("self.position.insert(id, Default::default());"));
+    }
+
     //	Transient	Transient	Ref	        MAYBE INVALID	    must point at owner, so refer is deleted along with it
     #[test]
     fn invalid_transient_can_have_mandatory_reference_to_transient_owner() {